@@ -0,0 +1,136 @@
+//! Anchors (`&name`) and the aliases (`*name`) that refer back to them.
+
+use crate::options::AliasMode;
+use crate::types::{YamlNode, YamlValue};
+use std::collections::HashMap;
+
+/// How deeply aliases may be nested inside anchored nodes. Fixed, as it keeps
+/// the recursion over an expanded tree bounded; see `AliasMode::Expand`.
+const MAX_ALIAS_DEPTH: usize = 64;
+
+/// Characters allowed in anchor and alias names. The YAML spec allows
+/// more, but `*.html` and the like are far more often meant as text.
+pub(crate) fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// The anchors defined so far in a document, and what copying them has
+/// cost.
+pub(crate) struct Anchors {
+    mode: AliasMode,
+    defined: HashMap<String, Anchored>,
+    /// Anchors whose nodes are still being parsed, with the deepest alias
+    /// nesting seen inside each
+    open: Vec<(String, usize)>,
+    /// Nodes created by expanding aliases
+    expanded: usize,
+}
+
+struct Anchored {
+    /// A copy to expand aliases into, kept only in `AliasMode::Expand`
+    node: Option<YamlNode>,
+    size: usize,
+    /// How many anchors deep aliases go inside the node, counting itself
+    depth: usize,
+}
+
+impl Anchors {
+    pub(crate) fn new(mode: AliasMode) -> Self {
+        Anchors {
+            mode,
+            defined: HashMap::new(),
+            open: Vec::new(),
+            expanded: 0,
+        }
+    }
+
+    fn check_enabled(&self, token: &str) -> Result<(), String> {
+        match self.mode {
+            AliasMode::Reject => Err(format!(
+                "Found `{}`, but anchors and aliases are not enabled; see ParseOptions::with_aliases",
+                token
+            )),
+            AliasMode::Expand { .. } | AliasMode::Preserve => Ok(()),
+        }
+    }
+
+    /// Start an anchor on the node about to be parsed
+    pub(crate) fn open(&mut self, name: &str) -> Result<(), String> {
+        self.check_enabled(&format!("&{}", name))?;
+        self.open.push((name.to_string(), 0));
+        Ok(())
+    }
+
+    /// Record the node the innermost open anchor was on. When aliases are
+    /// preserved, the anchor is kept on the node instead of copying it.
+    pub(crate) fn close(&mut self, node: &mut YamlNode) {
+        let Some((name, inner_depth)) = self.open.pop() else {
+            return;
+        };
+        let depth = inner_depth + 1;
+        if let Some((_, outer_depth)) = self.open.last_mut() {
+            *outer_depth = (*outer_depth).max(depth);
+        }
+        let copy = match self.mode {
+            AliasMode::Preserve => {
+                node.anchor = Some(name.clone());
+                None
+            }
+            AliasMode::Reject | AliasMode::Expand { .. } => Some(node.clone()),
+        };
+        let anchored = Anchored {
+            node: copy,
+            size: size(node),
+            depth,
+        };
+        self.defined.insert(name, anchored);
+    }
+
+    /// A copy of the node `*name` refers to, without its comments, or an
+    /// alias node when aliases are preserved
+    pub(crate) fn resolve(&mut self, name: &str) -> Result<YamlNode, String> {
+        self.check_enabled(&format!("*{}", name))?;
+        if self.open.iter().any(|(open, _)| open == name) {
+            return Err(format!("Alias *{} refers to a node containing it", name));
+        }
+        let anchored = self
+            .defined
+            .get(name)
+            .ok_or_else(|| format!("Alias *{} has no anchor", name))?;
+        let Some(node) = &anchored.node else {
+            return Ok(YamlNode::alias(name));
+        };
+
+        if anchored.depth > MAX_ALIAS_DEPTH {
+            return Err(format!(
+                "Alias *{} is nested more than {} anchors deep",
+                name, MAX_ALIAS_DEPTH
+            ));
+        }
+        if let AliasMode::Expand { max_expansion } = self.mode {
+            self.expanded += anchored.size;
+            if self.expanded > max_expansion {
+                return Err(format!(
+                    "Expanding alias *{} goes over the limit of {} nodes",
+                    name, max_expansion
+                ));
+            }
+        }
+        if let Some((_, inner_depth)) = self.open.last_mut() {
+            *inner_depth = (*inner_depth).max(anchored.depth);
+        }
+
+        let mut node = node.clone();
+        node.leading_comment = None;
+        node.inline_comment = None;
+        Ok(node)
+    }
+}
+
+fn size(node: &YamlNode) -> usize {
+    match &node.value {
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => 1,
+        YamlValue::Array(items) => 1 + items.iter().map(size).sum::<usize>(),
+        YamlValue::Object(map) => 1 + map.values().map(size).sum::<usize>(),
+    }
+}
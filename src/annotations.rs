@@ -0,0 +1,101 @@
+use crate::path;
+use crate::types::{YamlNode, YamlValue};
+
+impl YamlNode {
+    /// Structured annotations in the leading comment, as `(name, value)` pairs.
+    ///
+    /// An annotation is a comment line starting with `@`: `@name: value`,
+    /// `@name value`, or a bare `@name`, whose value is empty. Other comment
+    /// lines are ignored, and the comment itself is left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::parse;
+    ///
+    /// let yaml = "# Database password\n# @secret\n# @type: string\npassword: hunter2";
+    /// let config = parse(yaml).unwrap();
+    /// let password = config.get("password").unwrap();
+    /// assert_eq!(password.annotations(), vec![("secret", ""), ("type", "string")]);
+    /// ```
+    pub fn annotations(&self) -> Vec<(&str, &str)> {
+        self.leading_comment
+            .as_deref()
+            .into_iter()
+            .flat_map(str::lines)
+            .filter_map(parse_annotation)
+            .collect()
+    }
+
+    /// Value of the first annotation called `name`, if present.
+    pub fn annotation(&self, name: &str) -> Option<&str> {
+        self.annotations()
+            .into_iter()
+            .find(|(found, _)| *found == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Paths of all nodes carrying annotation `name`, with its value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::parse;
+    ///
+    /// let yaml = "db:\n  # @secret\n  password: hunter2\n  host: localhost";
+    /// let config = parse(yaml).unwrap();
+    /// assert_eq!(config.find_annotated("secret"), vec![("db.password".to_string(), "")]);
+    /// ```
+    pub fn find_annotated(&self, name: &str) -> Vec<(String, &str)> {
+        let mut found = Vec::new();
+        collect_annotated(self, String::new(), name, &mut found);
+        found
+    }
+}
+
+pub(crate) fn parse_annotation(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim().strip_prefix('@')?;
+    let name_end = rest
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .unwrap_or(rest.len());
+    let (name, value) = rest.split_at(name_end);
+    if name.is_empty() {
+        return None;
+    }
+    let value = match value.strip_prefix(':') {
+        Some(value) => value,
+        // Without a colon the name must end the line or be followed by a space
+        None if value.is_empty() || value.starts_with(char::is_whitespace) => value,
+        None => return None,
+    };
+    Some((name, value.trim()))
+}
+
+fn collect_annotated<'a>(
+    node: &'a YamlNode,
+    node_path: String,
+    name: &str,
+    found: &mut Vec<(String, &'a str)>,
+) {
+    if let Some(value) = node.annotation(name) {
+        found.push((node_path.clone(), value));
+    }
+    match &node.value {
+        YamlValue::Object(obj) => {
+            for (key, child) in obj.iter() {
+                collect_annotated(child, path::join(&node_path, key), name, found);
+            }
+        }
+        YamlValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_annotated(
+                    item,
+                    path::join(&node_path, &index.to_string()),
+                    name,
+                    found,
+                );
+            }
+        }
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+    }
+}
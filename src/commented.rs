@@ -0,0 +1,546 @@
+use crate::options::{AliasMode, EmitOptions, ParseOptions};
+use crate::path;
+use crate::quoting::{QuotingPolicy, Standard};
+use crate::types::{Layout, YamlNode, YamlObject, YamlValue};
+
+/// An entry that is present in a comment but disabled, such as
+/// `# log_level: debug` in a mapping or `# - experimental` in a sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentedEntry {
+    /// Path the entry takes once enabled. Sequence indices count commented-out
+    /// items as if they were enabled.
+    pub path: String,
+    /// The comment line holding the entry, without the `#` marker
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object,
+    Array,
+}
+
+/// The comment holding a commented-out line
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Host {
+    /// The leading comment of the node at this path
+    Leading(String),
+    /// The trailing comment at the end of the document
+    Trailing,
+}
+
+struct Candidate {
+    host: Host,
+    line: usize,
+    /// Path of the mapping or sequence the entry belongs to
+    parent: String,
+    /// Number of that block's entries written before the line
+    position: usize,
+    segment: String,
+    key: String,
+    node: YamlNode,
+    text: String,
+}
+
+impl YamlNode {
+    /// Find comment lines that parse as entries of the mapping or sequence
+    /// they are written in.
+    ///
+    /// A mapping entry is a single `key: value` line whose key has no spaces
+    /// and starts in the same case as another key of the mapping; a sequence
+    /// entry is a single `- item` line. A value of several unquoted words,
+    /// as in `TODO: fix this later`, is prose. A line belongs to the
+    /// block its indentation lines up with, so one written after the last
+    /// entry of a nested block belongs to that block. Nothing is changed,
+    /// and plain prose in comments is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::parse;
+    ///
+    /// let config = parse("# log_level: debug\nport: 8080").unwrap();
+    /// let entries = config.commented_entries();
+    /// assert_eq!(entries[0].path, "log_level");
+    /// assert_eq!(entries[0].text, "log_level: debug");
+    /// ```
+    pub fn commented_entries(&self) -> Vec<CommentedEntry> {
+        candidates(self)
+            .into_iter()
+            .map(|candidate| CommentedEntry {
+                path: path::join(&candidate.parent, &candidate.segment),
+                text: candidate.text,
+            })
+            .collect()
+    }
+
+    /// Turn a commented-out entry back into an active one.
+    ///
+    /// `path` is the path reported by [`YamlNode::commented_entries`]. Comment
+    /// lines just above the entry, in the same block, become its leading
+    /// comment; lines below it stay with the node that follows.
+    pub fn enable(&mut self, path: &str) -> Result<(), String> {
+        let (parent_path, segment) =
+            path::split_last(path).ok_or_else(|| "Cannot enable the root node".to_string())?;
+        let parent_path = path::normalize(parent_path);
+        let candidate = candidates(self)
+            .into_iter()
+            .find(|candidate| candidate.parent == parent_path && candidate.segment == segment)
+            .ok_or_else(|| format!("No commented-out entry at: {}", path))?;
+
+        let Candidate {
+            host,
+            line,
+            parent,
+            position,
+            key,
+            mut node,
+            ..
+        } = candidate;
+        if self
+            .get_path(&parent)
+            .and_then(YamlNode::as_object)
+            .is_some_and(|obj| obj.contains_key(&key))
+        {
+            return Err(format!("Entry is already enabled: {}", path));
+        }
+
+        let (comment, levels) = self.host_comment_mut(&host)?;
+        node.leading_comment = take_line(comment, levels, line);
+        let parent = self
+            .get_path_mut(&parent)
+            .ok_or_else(|| format!("Path not found: {}", parent))?;
+        match &mut parent.value {
+            YamlValue::Object(obj) => obj.insert_at(position, key, node),
+            YamlValue::Array(items) => items.insert(position, node),
+            YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+        }
+        Ok(())
+    }
+
+    /// Comment out the entry at `path`, keeping its value and comments.
+    ///
+    /// The entry becomes a comment line above the node that follows it, so
+    /// [`YamlNode::enable`] can restore it. The last entry of a nested block
+    /// is written after the block at the block's indentation. Only entries
+    /// that fit on one line can be disabled, including blank values and flow
+    /// collections. The only entry of a nested block can't be, since the
+    /// block would be left empty, nor an entry holding an anchor that an
+    /// alias still refers to.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::{emit, parse};
+    ///
+    /// let mut config = parse("log_level: debug\nport: 8080").unwrap();
+    /// config.disable("log_level").unwrap();
+    /// assert!(emit(&config).starts_with("# log_level: debug\nport:"));
+    ///
+    /// config.enable("log_level").unwrap();
+    /// assert_eq!(config.get("log_level").and_then(|n| n.as_str()), Some("debug"));
+    /// ```
+    pub fn disable(&mut self, path: &str) -> Result<(), String> {
+        let (parent_path, segment) =
+            path::split_last(path).ok_or_else(|| "Cannot disable the root node".to_string())?;
+        let segment = segment.as_ref();
+        let is_root = path::segments(parent_path).next().is_none();
+        let parent = self
+            .get_path(parent_path)
+            .ok_or_else(|| format!("Path not found: {}", parent_path))?;
+
+        let not_found = || format!("Path not found: {}", path);
+        let entries = entries(parent);
+        let index = entries
+            .iter()
+            .position(|(key, _)| key == segment)
+            .ok_or_else(not_found)?;
+        if entries.len() == 1 && !is_root {
+            return Err(format!(
+                "Cannot disable the only entry of a nested block: {}",
+                path
+            ));
+        }
+        let (container, key) = match &parent.value {
+            YamlValue::Object(_) => (Container::Object, Some(segment)),
+            YamlValue::Array(_) | YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+                (Container::Array, None)
+            }
+        };
+        let line = render_line(key, entries[index].1)
+            .ok_or_else(|| format!("Only single-line entries can be disabled: {}", path))?;
+        // The line must be found again by enable()
+        let siblings: Vec<String> = match key {
+            Some(key) => entries
+                .iter()
+                .map(|(sibling, _)| sibling.clone())
+                .filter(|sibling| sibling != key)
+                .collect(),
+            None => Vec::new(),
+        };
+        if parse_entry(&line, container, &siblings).is_none() {
+            return Err(format!(
+                "Entry would not read back from a comment: {}",
+                path
+            ));
+        }
+        if anchor_in_use(self, path) {
+            return Err(format!(
+                "Cannot disable an entry whose anchor is in use: {}",
+                path
+            ));
+        }
+        let (mut host, level) = self.following_host(parent_path, index)?;
+
+        let parent = self.get_path_mut(parent_path).ok_or_else(not_found)?;
+        let node = match &mut parent.value {
+            YamlValue::Object(obj) => obj.remove(segment),
+            YamlValue::Array(items) => {
+                // The next item moves up into the removed one's place
+                if level == 0 {
+                    host = Host::Leading(path::join(parent_path, segment));
+                }
+                Some(items.remove(index))
+            }
+            YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => None,
+        }
+        .ok_or_else(not_found)?;
+
+        // The entry's own comment lines move with it, into the same block
+        let mut lines: Vec<String> = node
+            .leading_comment
+            .iter()
+            .flat_map(|comment| comment.lines())
+            .map(str::to_string)
+            .collect();
+        let mut line_levels: Vec<isize> =
+            Layout::levels(&node.layout.comment_levels, node.leading_comment.as_deref())
+                .into_iter()
+                .map(|own| own + level)
+                .collect();
+        lines.push(line);
+        line_levels.push(level);
+
+        let (comment, levels) = self.host_comment_mut(&host)?;
+        if let Some(existing) = comment.take() {
+            line_levels.extend(Layout::levels(levels, Some(&existing)));
+            lines.push(existing);
+        }
+        *comment = Some(lines.join("\n"));
+        *levels = line_levels;
+        Ok(())
+    }
+
+    /// Where a line for entry `index` of the block at `parent_path` goes:
+    /// above the next entry, or after the end of the block, above whatever
+    /// follows it. Also returns the line's level there.
+    fn following_host(&self, parent_path: &str, index: usize) -> Result<(Host, isize), String> {
+        let mut block_path = parent_path.to_string();
+        let mut index = index;
+        let mut level = 0;
+        loop {
+            let block = self
+                .get_path(&block_path)
+                .ok_or_else(|| format!("Path not found: {}", block_path))?;
+            if let Some((segment, _)) = entries(block).get(index + 1) {
+                return Ok((Host::Leading(path::join(&block_path, segment)), level));
+            }
+            let Some((outer_path, segment)) = path::split_last(&block_path) else {
+                return Ok((Host::Trailing, level));
+            };
+            let outer = self
+                .get_path(outer_path)
+                .ok_or_else(|| format!("Path not found: {}", outer_path))?;
+            index = entries(outer)
+                .iter()
+                .position(|(key, _)| *key == segment)
+                .unwrap_or(0);
+            block_path = outer_path.to_string();
+            level += 1;
+        }
+    }
+
+    /// The comment text of a host and the levels of its lines
+    fn host_comment_mut(
+        &mut self,
+        host: &Host,
+    ) -> Result<(&mut Option<String>, &mut Vec<isize>), String> {
+        match host {
+            Host::Leading(host_path) => {
+                let node = self
+                    .get_path_mut(host_path)
+                    .ok_or_else(|| format!("Path not found: {}", host_path))?;
+                Ok((&mut node.leading_comment, &mut node.layout.comment_levels))
+            }
+            Host::Trailing => Ok((&mut self.inline_comment, &mut self.layout.trailing_levels)),
+        }
+    }
+}
+
+/// Every commented-out entry in the tree, in document order
+fn candidates(root: &YamlNode) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    walk(root, "", &mut Vec::new(), &mut candidates);
+
+    // The trailing comment follows the end of every block the document ends
+    // with
+    if let Some(trailing) = root.inline_comment.as_deref() {
+        let levels = Layout::levels(&root.layout.trailing_levels, Some(trailing));
+        for (line, (text, level)) in trailing.lines().zip(levels).enumerate() {
+            let (parent, block) = usize::try_from(level)
+                .ok()
+                .and_then(|down| closing_block(root, "", down))
+                .unwrap_or((String::new(), root));
+            let position = entries(block).len();
+            add_candidate(
+                &mut candidates,
+                Host::Trailing,
+                line,
+                text,
+                parent,
+                block,
+                position,
+            );
+        }
+    }
+
+    // Sequence indices count the commented-out items before them
+    for index in 0..candidates.len() {
+        if candidates[index].key.is_empty() {
+            let earlier = candidates[..index]
+                .iter()
+                .filter(|earlier| earlier.parent == candidates[index].parent)
+                .count();
+            candidates[index].segment = (candidates[index].position + earlier).to_string();
+        }
+    }
+    candidates
+}
+
+/// Collect the candidates in the leading comments below `node`. `open`
+/// holds the enclosing blocks with the index of the entry being walked.
+fn walk<'a>(
+    node: &'a YamlNode,
+    node_path: &str,
+    open: &mut Vec<(String, &'a YamlNode, usize)>,
+    candidates: &mut Vec<Candidate>,
+) {
+    let children = entries(node);
+    for (index, &(ref segment, child)) in children.iter().enumerate() {
+        let child_path = path::join(node_path, segment);
+        if let Some(comment) = child.leading_comment.as_deref() {
+            let levels = Layout::levels(&child.layout.comment_levels, Some(comment));
+            for (line, (text, level)) in comment.lines().zip(levels).enumerate() {
+                let (parent, block, position) = match usize::try_from(level) {
+                    Ok(0) => None,
+                    // After the end of a block nested in the previous entry
+                    Ok(down) => index
+                        .checked_sub(1)
+                        .and_then(|previous| {
+                            let (segment, previous) = &children[previous];
+                            closing_block(previous, &path::join(node_path, segment), down - 1)
+                        })
+                        .map(|(parent, block)| (parent, block, entries(block).len())),
+                    // Between the entries of an enclosing block
+                    Err(_) => open.len().checked_sub(level.unsigned_abs()).map(|outer| {
+                        let (parent, block, entry) = &open[outer];
+                        (parent.clone(), *block, entry + 1)
+                    }),
+                }
+                .unwrap_or((node_path.to_string(), node, index));
+                let host = Host::Leading(child_path.clone());
+                add_candidate(candidates, host, line, text, parent, block, position);
+            }
+        }
+        open.push((node_path.to_string(), node, index));
+        walk(child, &child_path, open, candidates);
+        open.pop();
+    }
+}
+
+fn add_candidate(
+    candidates: &mut Vec<Candidate>,
+    host: Host,
+    line: usize,
+    text: &str,
+    parent: String,
+    block: &YamlNode,
+    position: usize,
+) {
+    let (container, siblings) = match &block.value {
+        YamlValue::Object(obj) => (Container::Object, obj.keys().cloned().collect()),
+        YamlValue::Array(_) => (Container::Array, Vec::new()),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => return,
+    };
+    let Some((key, node)) = parse_entry(text, container, &siblings) else {
+        return;
+    };
+    candidates.push(Candidate {
+        host,
+        line,
+        parent,
+        position,
+        // Filled in once every candidate is known
+        segment: key.clone(),
+        key,
+        node,
+        text: text.to_string(),
+    });
+}
+
+/// The keys or indices of a collection's entries, with the entries
+fn entries(node: &YamlNode) -> Vec<(String, &YamlNode)> {
+    match &node.value {
+        YamlValue::Object(obj) => obj
+            .iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        YamlValue::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (index.to_string(), item))
+            .collect(),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => Vec::new(),
+    }
+}
+
+/// The collection `down` levels down the end of `node`, where 0 is `node`
+/// itself, with its path
+fn closing_block<'a>(
+    node: &'a YamlNode,
+    node_path: &str,
+    down: usize,
+) -> Option<(String, &'a YamlNode)> {
+    let mut block = (node_path.to_string(), node);
+    for _ in 0..down {
+        let (segment, last) = entries(block.1).pop()?;
+        block = (path::join(&block.0, &segment), last);
+    }
+    matches!(block.1.value, YamlValue::Object(_) | YamlValue::Array(_)).then_some(block)
+}
+
+fn parse_entry(
+    text: &str,
+    container: Container,
+    siblings: &[String],
+) -> Option<(String, YamlNode)> {
+    let options = ParseOptions::new().with_aliases(AliasMode::Preserve);
+    let node = crate::parse_with(text, &options).ok()?;
+    let (key, value, raw) = match (container, node.value) {
+        (Container::Object, YamlValue::Object(obj)) if obj.len() == 1 => {
+            let (key, value) = obj.into_iter().next()?;
+            // Prose such as "Default settings: see docs" has spaces in the key,
+            // and "Note: ..." differs in case from the keys around it
+            if key.chars().any(char::is_whitespace) || !matches_key_case(&key, siblings) {
+                return None;
+            }
+            let raw = text.split_once(':').map_or("", |(_, raw)| raw);
+            (key, value, raw)
+        }
+        (Container::Array, YamlValue::Array(mut items)) if items.len() == 1 => {
+            let raw = text.trim_start().strip_prefix('-').unwrap_or("");
+            (String::new(), items.pop()?, raw)
+        }
+        _ => return None,
+    };
+    // Words after the colon, as in "TODO: fix this later", are prose unless
+    // they are quoted
+    let sentence = value
+        .as_str()
+        .is_some_and(|s| s.contains(char::is_whitespace));
+    if sentence && !raw.trim_start().starts_with(['"', '\'']) {
+        return None;
+    }
+    Some((key, value))
+}
+
+/// Whether a key starts in the same case as at least one of its siblings
+fn matches_key_case(key: &str, siblings: &[String]) -> bool {
+    let upper = |key: &str| key.chars().next().is_some_and(char::is_uppercase);
+    siblings.is_empty() || siblings.iter().any(|sibling| upper(sibling) == upper(key))
+}
+
+/// Emit a node as the single line it would occupy in its container, or
+/// `None` if it takes more than one.
+fn render_line(key: Option<&str>, node: &YamlNode) -> Option<String> {
+    // An alias can't be read back on its own
+    if matches!(node.value, YamlValue::Alias(_)) {
+        return None;
+    }
+    let mut item = node.clone();
+    item.leading_comment = None;
+    let wrapper = match key {
+        Some(key) => YamlNode::from(YamlObject::new().with(key, item)),
+        None => YamlNode::from_value(YamlValue::Array(vec![item])),
+    };
+    let options = EmitOptions::new().with_quoting(SpacedWords);
+    let line = crate::emit_with(&wrapper, &options);
+    let line = line.trim_end_matches('\n');
+    (!line.contains('\n')).then(|| line.to_string())
+}
+
+/// Quotes strings with spaces as well, so a disabled entry isn't taken for
+/// prose
+#[derive(Debug)]
+struct SpacedWords;
+
+impl QuotingPolicy for SpacedWords {
+    fn needs_quotes(&self, value: &str) -> bool {
+        Standard.needs_quotes(value) || value.contains(char::is_whitespace)
+    }
+}
+
+/// Whether an alias outside the node at `node_path` refers to an anchor
+/// inside it
+fn anchor_in_use(root: &YamlNode, node_path: &str) -> bool {
+    let Some(node) = root.get_path(node_path) else {
+        return false;
+    };
+    let mut inside = Vec::new();
+    collect_nodes(node, &mut inside);
+    let mut aliases = Vec::new();
+    collect_aliases(root, String::new(), &mut aliases);
+    aliases
+        .iter()
+        .filter_map(|alias| root.resolve_alias(alias))
+        .any(|target| inside.iter().any(|node| std::ptr::eq(*node, target)))
+}
+
+fn collect_nodes<'a>(node: &'a YamlNode, found: &mut Vec<&'a YamlNode>) {
+    found.push(node);
+    for (_, child) in entries(node) {
+        collect_nodes(child, found);
+    }
+}
+
+fn collect_aliases(node: &YamlNode, node_path: String, found: &mut Vec<String>) {
+    if matches!(node.value, YamlValue::Alias(_)) {
+        found.push(node_path.clone());
+    }
+    for (segment, child) in entries(node) {
+        collect_aliases(child, path::join(&node_path, &segment), found);
+    }
+}
+
+/// Cut line `index` out of a comment, returning the lines just above it
+/// that are in the same block.
+fn take_line(
+    comment: &mut Option<String>,
+    levels: &mut Vec<isize>,
+    index: usize,
+) -> Option<String> {
+    let text = comment.take()?;
+    let mut lines: Vec<&str> = text.lines().collect();
+    let mut line_levels = Layout::levels(levels, Some(&text));
+    let start = (0..index)
+        .rev()
+        .take_while(|&above| line_levels[above] == line_levels[index])
+        .last()
+        .unwrap_or(index);
+    let above = lines[start..index].join("\n");
+    lines.drain(start..=index);
+    line_levels.drain(start..=index);
+    *comment = Some(lines.join("\n")).filter(|rest| !rest.is_empty());
+    *levels = line_levels;
+    Some(above).filter(|above| !above.is_empty())
+}
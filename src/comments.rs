@@ -0,0 +1,204 @@
+use crate::path;
+use crate::types::{Layout, YamlNode, YamlValue};
+
+/// Which comment of a node to address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSlot {
+    /// Comment lines written above the node
+    Leading,
+    /// Comment written after the value on the same line. At the document root
+    /// this slot holds the trailing comments at the end of the document.
+    Inline,
+}
+
+/// A comment found while walking a tree with [`YamlNode::comments`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentEntry<'a> {
+    pub path: String,
+    pub slot: CommentSlot,
+    pub text: &'a str,
+}
+
+impl YamlNode {
+    /// Replace the comment in `slot` of the node at `path`.
+    ///
+    /// An inline comment must be a single line, except at the root where the
+    /// slot holds the trailing comment lines.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::{parse, CommentSlot};
+    ///
+    /// let mut config = parse("server:\n  host: localhost").unwrap();
+    /// config
+    ///     .set_comment("server", CommentSlot::Leading, "Managed by deploy-tool, do not edit")
+    ///     .unwrap();
+    /// assert!(yamp::emit(&config).starts_with("# Managed by deploy-tool"));
+    /// ```
+    pub fn set_comment<S: Into<String>>(
+        &mut self,
+        path: &str,
+        slot: CommentSlot,
+        text: S,
+    ) -> Result<(), String> {
+        let (comment, levels) = self.comment_slot_mut(path, slot)?;
+        let text = text.into();
+        check_one_line(path, slot, &text)?;
+        *comment = Some(text);
+        levels.clear();
+        Ok(())
+    }
+
+    /// Add text to the comment in `slot` of the node at `path`.
+    ///
+    /// Leading comments (and trailing comments at the root) gain a new line.
+    /// Inline comments stay on one line, so the text is joined with a space
+    /// and must not contain a line break.
+    pub fn append_comment<S: Into<String>>(
+        &mut self,
+        path: &str,
+        slot: CommentSlot,
+        text: S,
+    ) -> Result<(), String> {
+        let separator = if one_line(path, slot) { " " } else { "\n" };
+        let (comment, levels) = self.comment_slot_mut(path, slot)?;
+        let text = text.into();
+        check_one_line(path, slot, &text)?;
+        // New lines go in the node's own block
+        let mut kept = Layout::levels(levels, comment.as_deref());
+        kept.extend(text.lines().map(|_| 0));
+        *levels = kept;
+        *comment = match comment.take() {
+            Some(existing) => Some(format!("{}{}{}", existing, separator, text)),
+            None => Some(text),
+        };
+        Ok(())
+    }
+
+    /// Remove both comments of the node at `path`, and of every node below it
+    /// when `recursive` is set.
+    pub fn remove_comments(&mut self, path: &str, recursive: bool) -> Result<(), String> {
+        let node = self
+            .get_path_mut(path)
+            .ok_or_else(|| format!("Path not found: {}", path))?;
+        if recursive {
+            node.clear_comments_recursive();
+        } else {
+            node.leading_comment = None;
+            node.inline_comment = None;
+        }
+        Ok(())
+    }
+
+    /// Iterate over every comment in the tree, in document order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::{parse, CommentSlot};
+    ///
+    /// let config = parse("# Listen port\nport: 8080 # default").unwrap();
+    /// let found: Vec<_> = config
+    ///     .comments()
+    ///     .map(|entry| (entry.path, entry.slot, entry.text))
+    ///     .collect();
+    /// assert_eq!(
+    ///     found,
+    ///     vec![
+    ///         ("port".to_string(), CommentSlot::Leading, "Listen port"),
+    ///         ("port".to_string(), CommentSlot::Inline, "default"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn comments(&self) -> impl Iterator<Item = CommentEntry<'_>> {
+        let mut entries = Vec::new();
+        collect_comments(self, String::new(), &mut entries);
+        // The root inline comment is emitted after everything else
+        if let Some(ref trailing) = self.inline_comment {
+            entries.push(CommentEntry {
+                path: String::new(),
+                slot: CommentSlot::Inline,
+                text: trailing,
+            });
+        }
+        entries.into_iter()
+    }
+
+    fn comment_slot_mut(
+        &mut self,
+        path: &str,
+        slot: CommentSlot,
+    ) -> Result<(&mut Option<String>, &mut Vec<isize>), String> {
+        let node = self
+            .get_path_mut(path)
+            .ok_or_else(|| format!("Path not found: {}", path))?;
+        Ok(match slot {
+            CommentSlot::Leading => (&mut node.leading_comment, &mut node.layout.comment_levels),
+            CommentSlot::Inline => (&mut node.inline_comment, &mut node.layout.trailing_levels),
+        })
+    }
+
+    fn clear_comments_recursive(&mut self) {
+        self.leading_comment = None;
+        self.inline_comment = None;
+        match &mut self.value {
+            YamlValue::Object(obj) => {
+                for (_, child) in obj.iter_mut() {
+                    child.clear_comments_recursive();
+                }
+            }
+            YamlValue::Array(items) => {
+                for item in items {
+                    item.clear_comments_recursive();
+                }
+            }
+            YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+        }
+    }
+}
+
+/// Whether the comment in `slot` is written on the node's own line
+fn one_line(path: &str, slot: CommentSlot) -> bool {
+    slot == CommentSlot::Inline && path::segments(path).next().is_some()
+}
+
+fn check_one_line(path: &str, slot: CommentSlot, text: &str) -> Result<(), String> {
+    if one_line(path, slot) && text.contains('\n') {
+        return Err(format!("Inline comments must be a single line: {}", path));
+    }
+    Ok(())
+}
+
+fn collect_comments<'a>(node: &'a YamlNode, path: String, entries: &mut Vec<CommentEntry<'a>>) {
+    let is_root = path.is_empty();
+    if let Some(ref leading) = node.leading_comment {
+        entries.push(CommentEntry {
+            path: path.clone(),
+            slot: CommentSlot::Leading,
+            text: leading,
+        });
+    }
+    if !is_root {
+        if let Some(ref inline) = node.inline_comment {
+            entries.push(CommentEntry {
+                path: path.clone(),
+                slot: CommentSlot::Inline,
+                text: inline,
+            });
+        }
+    }
+    match &node.value {
+        YamlValue::Object(obj) => {
+            for (key, child) in obj.iter() {
+                collect_comments(child, path::join(&path, key), entries);
+            }
+        }
+        YamlValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_comments(item, path::join(&path, &index.to_string()), entries);
+            }
+        }
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+    }
+}
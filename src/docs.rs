@@ -0,0 +1,283 @@
+//! Reference documentation generated from a commented config template.
+//!
+//! Each key of the template becomes a row holding its dotted path, the value
+//! written in the template as its default, and its leading and inline
+//! comments as the description. Nested objects and arrays of objects are
+//! documented as sections of their own.
+//!
+//! # Example
+//!
+//! ```rust
+//! use yamp::{docs, parse};
+//!
+//! let template = parse(
+//!     "# Server settings\nserver:\n  # Bind address\n  host: localhost\n  port: 8080 # Listen port",
+//! )
+//! .unwrap();
+//!
+//! let reference = docs::markdown(&template);
+//! assert!(reference.contains("## `server`\n\nServer settings\n"));
+//! assert!(reference.contains("| `server.host` | `localhost` | Bind address |"));
+//! assert!(reference.contains("| `server.port` | `8080` | Listen port |"));
+//! ```
+
+use crate::annotations;
+use crate::path;
+use crate::types::{YamlNode, YamlValue};
+use std::fmt::Write;
+
+/// A documented key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocRow {
+    /// Dotted key path. Fields of objects inside arrays use `[]`, as in
+    /// `features[].name`.
+    pub path: String,
+    /// Value written in the template, for scalars and lists of scalars
+    pub default: Option<String>,
+    /// Leading and inline comments, joined into one line
+    pub description: String,
+}
+
+/// A group of rows: the top-level keys, a nested object, or the fields of an
+/// array of objects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocSection {
+    /// Path of the object or array, empty for the top-level keys
+    pub path: String,
+    /// Comments on the key holding the section
+    pub description: String,
+    pub rows: Vec<DocRow>,
+}
+
+/// Collect the sections of a template, in document order.
+///
+/// The top-level section always comes first, even when it has no rows.
+pub fn sections(template: &YamlNode) -> Vec<DocSection> {
+    let mut sections = vec![DocSection {
+        path: String::new(),
+        description: String::new(),
+        rows: Vec::new(),
+    }];
+    match &template.value {
+        YamlValue::Array(_) => document("", "[]", template, 0, &mut sections),
+        _ => walk(template, "", 0, &mut sections),
+    }
+    sections
+}
+
+/// Render a Markdown reference with one table per section.
+pub fn markdown(template: &YamlNode) -> String {
+    let mut output = String::new();
+    for section in sections(template) {
+        if !section.path.is_empty() {
+            let depth = path::segments(&section.path).count();
+            let level = "#".repeat((depth + 1).min(6));
+            let _ = writeln!(output, "{} `{}`\n", level, section.path);
+            if !section.description.is_empty() {
+                let _ = writeln!(output, "{}\n", escape_markdown(&section.description));
+            }
+        }
+        if section.rows.is_empty() {
+            continue;
+        }
+        output.push_str("| Key | Default | Description |\n| --- | --- | --- |\n");
+        for row in &section.rows {
+            let default = match row.default {
+                Some(ref default) => code_span(default),
+                None => String::new(),
+            };
+            let _ = writeln!(
+                output,
+                "| `{}` | {} | {} |",
+                row.path,
+                default,
+                escape_markdown(&row.description)
+            );
+        }
+        output.push('\n');
+    }
+    finish(output)
+}
+
+/// Render a plain text reference, one key per paragraph.
+pub fn plain_text(template: &YamlNode) -> String {
+    let mut output = String::new();
+    for section in sections(template) {
+        if !section.path.is_empty() {
+            let _ = writeln!(output, "[{}]", section.path);
+            if !section.description.is_empty() {
+                let _ = writeln!(output, "{}", section.description);
+            }
+            output.push('\n');
+        }
+        for row in &section.rows {
+            match row.default {
+                Some(ref default) => {
+                    let _ = writeln!(output, "{} = {}", row.path, default);
+                }
+                None => {
+                    let _ = writeln!(output, "{}", row.path);
+                }
+            }
+            if !row.description.is_empty() {
+                let _ = writeln!(output, "    {}", row.description);
+            }
+            output.push('\n');
+        }
+    }
+    finish(output)
+}
+
+fn walk(node: &YamlNode, prefix: &str, section: usize, sections: &mut Vec<DocSection>) {
+    match &node.value {
+        YamlValue::Object(obj) => {
+            for (key, child) in obj.iter() {
+                document(prefix, key, child, section, sections);
+            }
+        }
+        YamlValue::Array(items) => {
+            // Every object in the array documents the same fields
+            for item in items.iter().filter(|item| item.is_object()) {
+                walk(item, prefix, section, sections);
+            }
+        }
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+    }
+}
+
+fn document(
+    prefix: &str,
+    key: &str,
+    node: &YamlNode,
+    section: usize,
+    sections: &mut Vec<DocSection>,
+) {
+    let node_path = path::join(prefix, key);
+    let description = describe(node);
+    match &node.value {
+        YamlValue::String(s) => add_row(
+            &mut sections[section],
+            DocRow {
+                path: node_path,
+                default: Some(s.clone()),
+                description,
+            },
+        ),
+        YamlValue::Alias(name) => add_row(
+            &mut sections[section],
+            DocRow {
+                path: node_path,
+                default: Some(format!("*{}", name)),
+                description,
+            },
+        ),
+        YamlValue::Blank => add_row(
+            &mut sections[section],
+            DocRow {
+                path: node_path,
+                default: None,
+                description,
+            },
+        ),
+        YamlValue::Array(items) if items.iter().any(YamlNode::is_object) => {
+            let node_path = if key == "[]" {
+                node_path
+            } else {
+                format!("{}[]", node_path)
+            };
+            let index = add_section(sections, &node_path, description);
+            walk(node, &node_path, index, sections);
+        }
+        YamlValue::Array(items) => {
+            let default = items
+                .iter()
+                .filter_map(YamlNode::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            add_row(
+                &mut sections[section],
+                DocRow {
+                    path: node_path,
+                    default: Some(default),
+                    description,
+                },
+            );
+        }
+        YamlValue::Object(_) => {
+            let index = add_section(sections, &node_path, description);
+            walk(node, &node_path, index, sections);
+        }
+    }
+}
+
+/// Add a row, or fill in a row already seen in an earlier array item.
+fn add_row(section: &mut DocSection, row: DocRow) {
+    match section
+        .rows
+        .iter_mut()
+        .find(|existing| existing.path == row.path)
+    {
+        Some(existing) => {
+            if existing.description.is_empty() {
+                existing.description = row.description;
+            }
+        }
+        None => section.rows.push(row),
+    }
+}
+
+fn add_section(sections: &mut Vec<DocSection>, section_path: &str, description: String) -> usize {
+    if let Some(index) = sections.iter().position(|s| s.path == section_path) {
+        if sections[index].description.is_empty() {
+            sections[index].description = description;
+        }
+        return index;
+    }
+    sections.push(DocSection {
+        path: section_path.to_string(),
+        description,
+        rows: Vec::new(),
+    });
+    sections.len() - 1
+}
+
+/// The prose of a node's comments. Annotation lines are left out.
+fn describe(node: &YamlNode) -> String {
+    let leading = node
+        .leading_comment
+        .iter()
+        .flat_map(|comment| comment.lines())
+        .filter(|line| annotations::parse_annotation(line).is_none());
+    let inline = node
+        .inline_comment
+        .iter()
+        .flat_map(|comment| comment.lines());
+    leading
+        .chain(inline)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// End the output with exactly one newline.
+fn finish(mut output: String) -> String {
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn code_span(value: &str) -> String {
+    let value = escape_markdown(&value.replace('\n', "\\n"));
+    if value.is_empty() {
+        "`\"\"`".to_string()
+    } else if value.contains('`') {
+        format!("`` {} ``", value)
+    } else {
+        format!("`{}`", value)
+    }
+}
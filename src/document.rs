@@ -0,0 +1,287 @@
+use crate::encoding::Encoding;
+use crate::options::{EmitOptions, LineEnding};
+use crate::types::YamlNode;
+use std::fmt;
+
+/// A parsed file: its root node plus the conventions it was written with,
+/// so it can be written back the same way.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_document, parse_document, LineEnding};
+///
+/// let document = parse_document("name: api\r\nport: 80\r\n").unwrap();
+/// assert_eq!(document.line_ending, LineEnding::CrLf);
+/// assert_eq!(document.root.get("name").and_then(|n| n.as_str()), Some("api"));
+/// assert_eq!(emit_document(&document), "name: api\r\nport: \"80\"");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub root: YamlNode,
+    /// Line break used by the source, detected from its first line
+    pub line_ending: LineEnding,
+    /// Encoding of the source when parsed from bytes
+    pub encoding: Encoding,
+    /// Whether the source started with a byte order mark
+    pub bom: bool,
+    /// Whether the document starts with a `---` marker. Documents after the
+    /// first in a stream always get one.
+    pub explicit_start: bool,
+    /// Whether the document ends with a `...` marker
+    pub explicit_end: bool,
+    /// `%YAML` and `%TAG` directives before the document's `---` marker
+    pub directives: Vec<Directive>,
+    /// Comment lines before the document's `---` marker, such as a license
+    /// header. Comments after a document not ended with `...` belong to
+    /// that document instead.
+    pub header_comment: Option<String>,
+}
+
+impl Document {
+    pub fn new(root: YamlNode) -> Self {
+        Document {
+            root,
+            line_ending: LineEnding::default(),
+            encoding: Encoding::default(),
+            bom: false,
+            explicit_start: false,
+            explicit_end: false,
+            directives: Vec::new(),
+            header_comment: None,
+        }
+    }
+
+    /// The full tag a node's tag stands for, with its handle expanded by
+    /// the document's `%TAG` directives or the default `!` and `!!`
+    /// handles. `None` for a named handle no directive defines.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::parse_document;
+    ///
+    /// let yaml = "%TAG !e! tag:example.com,2000:app/\n---\nitem: !e!widget x";
+    /// let document = parse_document(yaml).unwrap();
+    /// let tag = document.root.get("item").and_then(|item| item.tag.as_deref());
+    /// assert_eq!(tag, Some("!e!widget"));
+    /// assert_eq!(
+    ///     document.expand_tag("!e!widget").as_deref(),
+    ///     Some("tag:example.com,2000:app/widget")
+    /// );
+    /// assert_eq!(
+    ///     document.expand_tag("!!str").as_deref(),
+    ///     Some("tag:yaml.org,2002:str")
+    /// );
+    /// ```
+    pub fn expand_tag(&self, tag: &str) -> Option<String> {
+        if let Some(verbatim) = tag.strip_prefix("!<") {
+            return verbatim.strip_suffix('>').map(str::to_string);
+        }
+        let handle_len = match tag.get(1..)?.find('!') {
+            Some(index) => index + 2,
+            None => 1,
+        };
+        let (handle, suffix) = tag.split_at(handle_len);
+        let defined = self
+            .directives
+            .iter()
+            .rev()
+            .find_map(|directive| match directive {
+                Directive::Tag {
+                    handle: name,
+                    prefix,
+                } if name == handle => Some(prefix.as_str()),
+                _ => None,
+            });
+        let prefix = match (defined, handle) {
+            (Some(prefix), _) => prefix,
+            (None, "!") => "!",
+            (None, "!!") => "tag:yaml.org,2002:",
+            (None, _) => return None,
+        };
+        Some(format!("{}{}", prefix, suffix))
+    }
+
+    /// Default options adjusted to the document's conventions, to build on
+    /// when it should be written back in its own style.
+    pub fn emit_options(&self) -> EmitOptions {
+        EmitOptions::new().with_line_ending(self.line_ending)
+    }
+}
+
+/// A directive line before a document's `---` marker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    /// `%YAML 1.2`: the YAML version the document was written for
+    Yaml(String),
+    /// `%TAG !e! tag:example.com,2000:`: a handle for tags to start with,
+    /// and the prefix it stands for
+    Tag { handle: String, prefix: String },
+    /// Any other directive, kept as written without its `%`
+    Reserved(String),
+}
+
+impl Directive {
+    /// Read a directive line, starting with `%`
+    fn parse(line: &str) -> Result<Self, String> {
+        // A comment after the parameters isn't kept
+        let text = match line.find(" #") {
+            Some(index) => &line[..index],
+            None => line,
+        };
+        let mut words = text[1..].split_whitespace();
+        let directive = match words.next() {
+            Some("YAML") => match (words.next(), words.next()) {
+                (Some(version), None) => Directive::Yaml(version.to_string()),
+                _ => return Err(format!("Expected a version in {:?}", text.trim())),
+            },
+            Some("TAG") => match (words.next(), words.next(), words.next()) {
+                (Some(handle), Some(prefix), None) if is_tag_handle(handle) => Directive::Tag {
+                    handle: handle.to_string(),
+                    prefix: prefix.to_string(),
+                },
+                _ => return Err(format!("Expected a handle and prefix in {:?}", text.trim())),
+            },
+            Some(_) => Directive::Reserved(text[1..].trim().to_string()),
+            None => return Err("Expected a directive name after %".to_string()),
+        };
+        Ok(directive)
+    }
+
+    /// Whether a document can't have both: two `%YAML` directives, or two
+    /// `%TAG`s for one handle
+    fn repeats(&self, other: &Directive) -> bool {
+        match (self, other) {
+            (Directive::Yaml(_), Directive::Yaml(_)) => true,
+            (Directive::Tag { handle, .. }, Directive::Tag { handle: other, .. }) => {
+                handle == other
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Directive::Yaml(version) => write!(f, "%YAML {}", version),
+            Directive::Tag { handle, prefix } => write!(f, "%TAG {} {}", handle, prefix),
+            Directive::Reserved(text) => write!(f, "%{}", text),
+        }
+    }
+}
+
+/// `!`, `!!` or a named handle such as `!e!`
+fn is_tag_handle(handle: &str) -> bool {
+    let Some(name) = handle.strip_prefix('!').and_then(|rest| rest.strip_suffix('!')) else {
+        return handle == "!";
+    };
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// The text of one document in a stream, without its markers
+#[derive(Default)]
+pub(crate) struct Part {
+    pub(crate) text: String,
+    pub(crate) explicit_start: bool,
+    pub(crate) explicit_end: bool,
+    pub(crate) directives: Vec<Directive>,
+    pub(crate) header_comment: Option<String>,
+}
+
+/// Split a stream into documents at `---` and `...` lines.
+///
+/// Markers only count at the start of a line, where they always end the
+/// document before them. A document started with `---` is kept even when
+/// empty. Comments outside of any document are the header of the next
+/// `---` document, go with the next document without one, or with the last
+/// one at the end of the stream.
+///
+/// Lines starting with `%` are directives for the next document, which
+/// must then start with `---`.
+pub(crate) fn split_documents(yaml: &str) -> Result<Vec<Part>, String> {
+    let mut parts = Vec::new();
+    let mut current = Part::default();
+    // Comments waiting for a document to belong to
+    let mut pending = String::new();
+    let mut directives = Vec::new();
+
+    for raw_line in yaml.split_inclusive('\n') {
+        let line = raw_line.trim_end_matches('\n');
+        if line.starts_with('%') {
+            let directive = Directive::parse(line)?;
+            if directives.iter().any(|other| directive.repeats(other)) {
+                return Err(format!("Repeated directive: {}", directive));
+            }
+            directives.push(directive);
+        } else if let Some(rest) = marker(line, "---") {
+            finish(&mut parts, &mut current, &mut pending);
+            current.explicit_start = true;
+            current.directives = std::mem::take(&mut directives);
+            current.header_comment = comment_text(&std::mem::take(&mut pending));
+            // `--- value` or `--- # comment` starts the document's content
+            if !rest.trim().is_empty() {
+                push_line(&mut current.text, rest.trim_start());
+            }
+        } else if let Some(rest) = marker(line, "...") {
+            current.explicit_end = true;
+            finish(&mut parts, &mut current, &mut pending);
+            if !rest.trim().is_empty() {
+                push_line(&mut pending, rest.trim_start());
+            }
+        } else if !directives.is_empty() && is_content(line) {
+            return Err("Directives must be followed by a --- marker".to_string());
+        } else {
+            current.text.push_str(raw_line);
+        }
+    }
+    if !directives.is_empty() {
+        return Err("Directives must be followed by a --- marker".to_string());
+    }
+    finish(&mut parts, &mut current, &mut pending);
+
+    if let Some(last) = parts.last_mut() {
+        last.text.push_str(&pending);
+    }
+    Ok(parts)
+}
+
+fn marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    line.strip_prefix(marker)
+        .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+fn push_line(text: &mut String, line: &str) {
+    text.push_str(line);
+    text.push('\n');
+}
+
+fn is_content(line: &str) -> bool {
+    !line.trim().is_empty() && !line.trim_start().starts_with('#')
+}
+
+/// Comment lines joined without their `#`, as the parser keeps them
+fn comment_text(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+fn finish(parts: &mut Vec<Part>, current: &mut Part, pending: &mut String) {
+    let part = std::mem::take(current);
+    let has_content = part.text.lines().any(is_content);
+    if part.explicit_start {
+        parts.push(part);
+    } else if has_content {
+        parts.push(Part {
+            text: std::mem::take(pending) + &part.text,
+            ..part
+        });
+    } else {
+        pending.push_str(&part.text);
+    }
+}
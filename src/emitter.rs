@@ -1,78 +1,524 @@
-use crate::types::{YamlNode, YamlValue};
-use std::fmt::Write;
+use crate::options::{EmitOptions, FlowStyle, QuoteStyle, ScalarStyle, SequenceIndent};
+use crate::path;
+use crate::quoting::{requires_quotes, requires_quotes_in_flow};
+use crate::types::{CollectionStyle, Layout, YamlNode, YamlObject, YamlValue};
+use std::borrow::Cow;
+use std::fmt::{self, Write};
 
-pub(crate) struct Emitter {
-    output: String,
-    indent_size: usize,
+/// Width of `- ` before an object that starts on the dash line
+const DASH_WIDTH: usize = 2;
+
+/// Writes YAML to any `fmt::Write` as it goes
+pub(crate) struct Emitter<W: Write> {
+    output: W,
+    options: EmitOptions,
     current_indent: usize,
+    /// Characters written since the last line break
+    column: usize,
+    /// Keys and indices leading to the node being written, tracked only
+    /// when there are style overrides to look up
+    path: Vec<String>,
+    /// Column inline comments are lined up at in the current block
+    comment_column: Option<usize>,
+    /// Set after a `|+` block scalar until something follows it. Its last
+    /// line break is still to be written, or the value loses a newline.
+    open_block: bool,
+    /// Indents of the block collections being written, innermost last
+    blocks: Vec<usize>,
+    /// Indents of the block collections that ended with the last entry
+    /// written, outermost first
+    closed: Vec<usize>,
 }
 
-impl Emitter {
+impl Emitter<String> {
     pub(crate) fn new() -> Self {
+        Self::with_options(EmitOptions::default())
+    }
+
+    pub(crate) fn with_options(options: EmitOptions) -> Self {
+        // Pre-allocate reasonable capacity
+        Emitter::with_output(String::with_capacity(1024), options)
+    }
+
+    pub(crate) fn emit(&mut self, node: &YamlNode) -> String {
+        self.output.clear(); // Clear previous content instead of creating new String
+
+        // Writing to a String cannot fail
+        self.write_document(node).unwrap();
+        std::mem::take(&mut self.output) // Move instead of clone
+    }
+}
+
+impl<W: Write> Emitter<W> {
+    pub(crate) fn with_output(output: W, options: EmitOptions) -> Self {
+        // Canonical output has a fixed layout; only stripping comments is allowed
+        let options = if options.canonical {
+            EmitOptions {
+                comments: options.comments,
+                ..EmitOptions::canonical()
+            }
+        } else {
+            options
+        };
         Emitter {
-            output: String::with_capacity(1024), // Pre-allocate reasonable capacity
-            indent_size: 2,
+            output,
+            options,
             current_indent: 0,
+            column: 0,
+            path: Vec::new(),
+            comment_column: None,
+            open_block: false,
+            blocks: Vec::new(),
+            closed: Vec::new(),
         }
     }
 
-    pub(crate) fn emit(&mut self, node: &YamlNode) -> String {
-        self.output.clear(); // Clear previous content instead of creating new String
+    pub(crate) fn write_document(&mut self, node: &YamlNode) -> fmt::Result {
+        self.current_indent = 0;
+        self.column = 0;
+        self.path.clear();
+        self.comment_column = None;
+        self.open_block = false;
+        self.blocks.clear();
+        self.closed.clear();
 
         // Emit the node content without inline comments (they'll be handled as trailing)
-        self.emit_node_without_inline_comment(node, false);
+        let root_flow = match node.style {
+            CollectionStyle::Flow if !node.is_string() && !self.options.canonical => {
+                self.format_flow(node)
+            }
+            _ if is_empty_collection(node) => self.format_flow(node),
+            CollectionStyle::Flow | CollectionStyle::Auto | CollectionStyle::Block => None,
+        };
+        if let Some(ref comment) = node.leading_comment {
+            self.write_comment(comment, false)?;
+        }
+        self.write_root_properties(node, root_flow.is_some())?;
+        if let Some(text) = root_flow {
+            self.write(&text)?;
+        } else {
+            self.emit_node_without_comments(node, false)?;
+        }
 
         // For root-level nodes, inline comments are treated as trailing comments
         // and emitted at the end of the document
-        if let Some(ref trailing) = node.inline_comment {
-            if !self.output.is_empty() && !self.output.ends_with('\n') {
-                self.output.push('\n');
+        self.write_trailing_comment(node, 0)?;
+        self.close_block()
+    }
+
+    /// Write a node with every line indented by `base_indent`, for embedding
+    /// in a larger file. The root's inline comment stays on the root's line;
+    /// after a block collection, which has none, it is written after the
+    /// collection as in a document.
+    pub(crate) fn write_fragment(&mut self, node: &YamlNode, base_indent: usize) -> fmt::Result {
+        self.current_indent = base_indent;
+        self.column = 0;
+        self.path.clear();
+        self.comment_column = None;
+        self.open_block = false;
+        self.blocks.clear();
+        self.closed.clear();
+
+        if let Some(ref comment) = node.leading_comment {
+            self.write_comment(comment, false)?;
+        }
+        let flow = match node.style {
+            CollectionStyle::Flow if !node.is_string() && !self.options.canonical => {
+                self.format_flow(node)
+            }
+            _ if is_empty_collection(node) => self.format_flow(node),
+            CollectionStyle::Flow | CollectionStyle::Auto | CollectionStyle::Block => None,
+        };
+        self.write_root_properties(node, flow.is_some())?;
+        if let Some(text) = flow {
+            self.write(&text)?;
+            if let Some(ref comment) = node.inline_comment {
+                self.write_comment(comment, true)?;
+            }
+            return Ok(());
+        }
+        match &node.value {
+            YamlValue::String(s) => self.emit_string(node, s)?,
+            YamlValue::Alias(name) => self.emit_alias(node, name)?,
+            YamlValue::Blank => self.emit_empty(node)?,
+            YamlValue::Array(_) | YamlValue::Object(_) => {
+                self.emit_node_with_comment_control(node, false, false)?;
+                self.write_trailing_comment(node, base_indent)?;
             }
-            for line in trailing.lines() {
-                writeln!(&mut self.output, "# {}", line).unwrap();
+        }
+        self.close_block()
+    }
+
+    /// Write the inline comment of a root as lines after everything else,
+    /// each in the block it was written in, or at `indent` outside them
+    fn write_trailing_comment(&mut self, node: &YamlNode, indent: usize) -> fmt::Result {
+        let Some(ref trailing) = node.inline_comment else {
+            return Ok(());
+        };
+        if !self.options.comments {
+            return Ok(());
+        }
+        if self.column > 0 || self.open_block {
+            self.newline()?;
+        }
+        // Lines can belong to the blocks the output ends with
+        let levels = Layout::levels(&node.layout.trailing_levels, Some(trailing));
+        for (line, level) in trailing.lines().zip(levels) {
+            self.current_indent = usize::try_from(level)
+                .ok()
+                .and_then(|level| self.closed.get(level).copied())
+                .unwrap_or(indent);
+            self.write_indent()?;
+            self.write_comment_line(line)?;
+            self.newline()?;
+        }
+        self.current_indent = indent;
+        Ok(())
+    }
+
+    /// Write the last line break of a `|+` block scalar that ended the
+    /// output
+    fn close_block(&mut self) -> fmt::Result {
+        if self.open_block {
+            self.newline()?;
+        }
+        Ok(())
+    }
+
+    /// Start the line of a document or fragment root: its anchor and tag,
+    /// if any, then the value when it goes on the same line. A block
+    /// collection starts on the line after them.
+    fn write_root_properties(&mut self, node: &YamlNode, flow: bool) -> fmt::Result {
+        let same_line = flow || !(node.is_array() || node.is_object());
+        match properties(node) {
+            Some(properties) => {
+                self.write_indent()?;
+                self.write(&properties)?;
+                if node.is_blank() {
+                    Ok(())
+                } else if same_line {
+                    self.write(" ")
+                } else {
+                    self.newline()
+                }
             }
+            None if same_line => self.write_indent(),
+            None => Ok(()),
         }
+    }
 
-        std::mem::take(&mut self.output) // Move instead of clone
+    /// Write ` &name !tag` for a node with an anchor or tag after `key:` or
+    /// `-`
+    fn write_properties(&mut self, node: &YamlNode) -> fmt::Result {
+        if let Some(properties) = properties(node) {
+            self.write(" ")?;
+            self.write(&properties)?;
+        }
+        Ok(())
     }
 
-    fn write_indent(&mut self) {
+    /// Write the inline comment of a blank node, after its anchor or tag if
+    /// it has them
+    fn emit_empty(&mut self, node: &YamlNode) -> fmt::Result {
+        if let Some(ref comment) = node.inline_comment {
+            self.write_comment(comment, true)?;
+        }
+        Ok(())
+    }
+
+    /// Write `*name`, followed by the alias's inline comment
+    fn emit_alias(&mut self, node: &YamlNode, name: &str) -> fmt::Result {
+        self.write("*")?;
+        self.write(name)?;
+        if let Some(ref comment) = node.inline_comment {
+            self.write_comment(comment, true)?;
+        }
+        Ok(())
+    }
+
+    /// Write text, keeping track of the column
+    fn write(&mut self, s: &str) -> fmt::Result {
+        if !s.is_empty() {
+            self.open_block = false;
+        }
+        match s.rfind('\n') {
+            Some(index) => self.column = s[index + 1..].chars().count(),
+            None => self.column += s.chars().count(),
+        }
+        self.output.write_str(s)
+    }
+
+    fn write_indent(&mut self) -> fmt::Result {
         if self.current_indent > 0 {
             // Use a pre-allocated string for common indent levels
-            if self.current_indent <= 64 {
-                const SPACES: &str =
-                    "                                                                ";
-                self.output.push_str(&SPACES[..self.current_indent]);
-            } else {
-                // Fallback for very deep indentation
-                for _ in 0..self.current_indent {
-                    self.output.push(' ');
-                }
+            const SPACES: &str = "                                                                ";
+            let mut remaining = self.current_indent;
+            while remaining > 0 {
+                let chunk = remaining.min(SPACES.len());
+                self.write(&SPACES[..chunk])?;
+                remaining -= chunk;
             }
         }
+        Ok(())
     }
 
-    fn write_comment(&mut self, comment: &str, inline: bool) {
+    fn newline(&mut self) -> fmt::Result {
+        self.write(self.options.line_ending.as_str())
+    }
+
+    fn write_comment_line(&mut self, line: &str) -> fmt::Result {
+        let marker = if self.options.comment_space {
+            "# "
+        } else {
+            "#"
+        };
+        self.write(marker)?;
+        self.write(line)
+    }
+
+    fn write_comment(&mut self, comment: &str, inline: bool) -> fmt::Result {
+        if !self.options.comments {
+            return Ok(());
+        }
         if inline {
             // Inline comments should be single line only
             let single_line = comment.lines().next().unwrap_or("");
-            write!(&mut self.output, " # {}", single_line).unwrap();
+            let padding = self
+                .comment_column
+                .map_or(1, |column| column.saturating_sub(self.column).max(1));
+            self.write(&" ".repeat(padding))?;
+            self.write_comment_line(single_line)?;
         } else {
             // Leading comments can be multiline - each line gets its own # prefix
             for line in comment.lines() {
-                self.write_indent();
-                writeln!(&mut self.output, "# {}", line).unwrap();
+                for part in self.wrap_comment(line) {
+                    self.write_indent()?;
+                    self.write_comment_line(&part)?;
+                    self.newline()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the leading comment of an entry. Lines that belong to another
+    /// block, such as entries commented out after the end of a nested one,
+    /// are written at that block's indent.
+    fn write_leading_comment(&mut self, node: &YamlNode) -> fmt::Result {
+        let closed = std::mem::take(&mut self.closed);
+        let Some(ref comment) = node.leading_comment else {
+            return Ok(());
+        };
+        let indent = self.current_indent;
+        let levels = Layout::levels(&node.layout.comment_levels, Some(comment));
+        for (line, level) in comment.lines().zip(levels) {
+            let block = match usize::try_from(level) {
+                Ok(0) => None,
+                Ok(down) => closed.get(down - 1),
+                Err(_) => {
+                    let up = level.unsigned_abs();
+                    self.blocks
+                        .len()
+                        .checked_sub(up + 1)
+                        .map(|i| &self.blocks[i])
+                }
+            };
+            self.current_indent = block.copied().unwrap_or(indent);
+            self.write_comment(line, false)?;
+        }
+        self.current_indent = indent;
+        Ok(())
+    }
+
+    /// A leading comment line split to fit the comment width
+    fn wrap_comment<'s>(&self, line: &'s str) -> Vec<Cow<'s, str>> {
+        let Some(width) = self.options.comment_width else {
+            return vec![Cow::Borrowed(line)];
+        };
+        let marker = if self.options.comment_space { 2 } else { 1 };
+        let width = width.saturating_sub(self.current_indent + marker);
+        // Keep indented text, annotations and oddly spaced lines as written
+        let words: Vec<&str> = line.split(' ').collect();
+        if line.chars().count() <= width
+            || line.starts_with(char::is_whitespace)
+            || line.starts_with('@')
+            || words.iter().any(|word| word.is_empty())
+        {
+            return vec![Cow::Borrowed(line)];
+        }
+        wrap_words(&words, width)
+            .into_iter()
+            .map(Cow::Owned)
+            .collect()
+    }
+
+    fn write_quoted(&mut self, s: &str) -> fmt::Result {
+        let quoted = self.quoted(s);
+        self.write(&quoted)
+    }
+
+    fn quoted(&self, s: &str) -> String {
+        let single = self.options.quote_style == QuoteStyle::Single
+            && !s
+                .chars()
+                .any(|c| matches!(c, '\'' | '\\') || c.is_control());
+        if single {
+            format!("'{}'", s)
+        } else {
+            format!("\"{}\"", escape_string(s))
+        }
+    }
+
+    /// A string as written on one line, in the style an override asks for
+    /// when it can hold the value
+    fn scalar_text<'s>(&self, s: &'s str, style: Option<ScalarStyle>) -> Cow<'s, str> {
+        match style {
+            Some(ScalarStyle::Plain) if !requires_quotes(s) => Cow::Borrowed(s),
+            Some(ScalarStyle::Quoted) => Cow::Owned(self.quoted(s)),
+            _ if self.needs_quotes(s) => Cow::Owned(self.quoted(s)),
+            _ => Cow::Borrowed(s),
+        }
+    }
+
+    /// Whether a string is quoted, by necessity or by the quoting policy
+    fn needs_quotes(&self, s: &str) -> bool {
+        requires_quotes(s) || self.options.quoting.needs_quotes(s)
+    }
+
+    /// A string as written inside a flow collection
+    fn flow_scalar(&self, s: &str) -> String {
+        if requires_quotes_in_flow(s) || self.options.quoting.needs_quotes(s) {
+            self.quoted(s)
+        } else {
+            s.to_string()
+        }
+    }
+
+    fn tracks_path(&self) -> bool {
+        !self.options.styles.is_empty()
+    }
+
+    /// The style forced on the string being written, if any
+    fn style_override(&self) -> Option<ScalarStyle> {
+        self.options
+            .styles
+            .iter()
+            .rev()
+            .find(|(pattern, _)| path::match_prefix(pattern, &self.path) == Some(0))
+            .map(|&(_, style)| style)
+    }
+
+    /// Whether a style override applies somewhere inside the current node
+    fn has_override_below(&self) -> bool {
+        self.options.styles.iter().any(|(pattern, _)| {
+            path::match_prefix(pattern, &self.path).is_some_and(|rest| rest > 0)
+        })
+    }
+
+    fn has_comments(&self, node: &YamlNode) -> bool {
+        self.options.comments && (node.leading_comment.is_some() || node.inline_comment.is_some())
+    }
+
+    /// The flow form of a collection, if it's to be written in flow style
+    /// starting at `column`.
+    fn flow_text(&self, node: &YamlNode, column: usize) -> Option<String> {
+        if is_empty_collection(node) {
+            return self.format_flow(node);
+        }
+        if self.options.canonical {
+            return None;
+        }
+        match node.style {
+            CollectionStyle::Block => None,
+            CollectionStyle::Flow => self.format_flow(node),
+            CollectionStyle::Auto => {
+                let FlowStyle::Short { max_width } = self.options.flow_style else {
+                    return None;
+                };
+                let is_plain = |child: &YamlNode| {
+                    (child.is_string() || child.is_alias()) && !self.has_comments(child)
+                };
+                if self.has_override_below() {
+                    return None;
+                }
+                let short = match &node.value {
+                    YamlValue::Array(items) => !items.is_empty() && items.iter().all(is_plain),
+                    YamlValue::Object(map) => !map.is_empty() && map.values().all(is_plain),
+                    YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => false,
+                };
+                if !short {
+                    return None;
+                }
+                let text = self.format_flow(node)?;
+                (column + text.chars().count() <= max_width).then_some(text)
+            }
+        }
+    }
+
+    /// Write a node as `[a, b]`, `{k: v}` or a scalar, with nested
+    /// collections in flow style too. `None` if an element has comments,
+    /// which would be lost, or is a blank sequence item, which can't be
+    /// written.
+    fn format_flow(&self, node: &YamlNode) -> Option<String> {
+        let element = |child: &YamlNode| {
+            if self.has_comments(child) {
+                return None;
+            }
+            if child.is_blank() {
+                return properties(child);
+            }
+            let text = self.format_flow(child)?;
+            match properties(child) {
+                Some(properties) => Some(format!("{} {}", properties, text)),
+                None => Some(text),
+            }
+        };
+        match &node.value {
+            YamlValue::String(s) => Some(self.flow_scalar(s)),
+            YamlValue::Alias(name) => Some(format!("*{}", name)),
+            YamlValue::Blank => None,
+            YamlValue::Array(items) => {
+                let items = items.iter().map(element).collect::<Option<Vec<_>>>()?;
+                Some(format!("[{}]", items.join(", ")))
+            }
+            YamlValue::Object(map) => {
+                let pairs = map
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = self.flow_scalar(key);
+                        // `{key}` has a blank value
+                        if value.is_blank() && properties(value).is_none() {
+                            return (!self.has_comments(value)).then_some(key);
+                        }
+                        Some(format!("{}: {}", key, element(value)?))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("{{{}}}", pairs.join(", ")))
             }
         }
     }
 
-    fn emit_node(&mut self, node: &YamlNode, inline: bool) {
-        self.emit_node_with_comment_control(node, inline, true);
+    /// Write a collection in flow style, followed by its inline comment, if
+    /// it's to be written that way at the current position
+    fn emit_flow(&mut self, node: &YamlNode) -> Result<bool, fmt::Error> {
+        if node.is_string() || node.is_alias() || node.is_blank() {
+            return Ok(false);
+        }
+        let Some(text) = self.flow_text(node, self.column + 1) else {
+            return Ok(false);
+        };
+        self.write(" ")?;
+        self.write(&text)?;
+        if let Some(ref comment) = node.inline_comment {
+            self.write_comment(comment, true)?;
+        }
+        Ok(true)
     }
 
-    fn emit_node_without_inline_comment(&mut self, node: &YamlNode, inline: bool) {
-        // Emit with inline comments suppressed (used at root level)
-        self.emit_node_with_comment_control_internal(node, inline, true, false);
+    fn emit_node_without_comments(&mut self, node: &YamlNode, inline: bool) -> fmt::Result {
+        // Emit with comments suppressed (used at root level, which writes
+        // the leading comment before the anchor)
+        self.emit_node_with_comment_control_internal(node, inline, false, false)
     }
 
     fn emit_node_with_comment_control(
@@ -80,8 +526,8 @@ impl Emitter {
         node: &YamlNode,
         inline: bool,
         emit_leading_comment: bool,
-    ) {
-        self.emit_node_with_comment_control_internal(node, inline, emit_leading_comment, true);
+    ) -> fmt::Result {
+        self.emit_node_with_comment_control_internal(node, inline, emit_leading_comment, true)
     }
 
     fn emit_node_with_comment_control_internal(
@@ -90,11 +536,11 @@ impl Emitter {
         inline: bool,
         emit_leading_comment: bool,
         emit_inline_comment: bool,
-    ) {
+    ) -> fmt::Result {
         // Write leading comment if present and requested
         if !inline && emit_leading_comment {
             if let Some(ref comment) = node.leading_comment {
-                self.write_comment(comment, false);
+                self.write_comment(comment, false)?;
             }
         }
 
@@ -102,239 +548,511 @@ impl Emitter {
             YamlValue::String(s) => {
                 // Check if string should be emitted as multiline
                 if !inline && should_use_multiline(s.as_ref()) {
-                    self.emit_multiline_string(s.as_ref());
-                } else if needs_quoting(s.as_ref()) {
-                    write!(&mut self.output, "\"{}\"", escape_string(s.as_ref())).unwrap();
+                    self.emit_multiline_string(s.as_ref())?;
+                } else if self.needs_quotes(s.as_ref()) {
+                    self.write_quoted(s.as_ref())?;
                 } else {
-                    self.output.push_str(s.as_ref());
+                    self.write(s.as_ref())?;
                 }
             }
+            YamlValue::Alias(name) => {
+                self.write("*")?;
+                self.write(name)?;
+            }
+            YamlValue::Blank => {}
             YamlValue::Array(items) => {
-                self.emit_array(items);
+                self.emit_array(items)?;
             }
             YamlValue::Object(_) => {
-                self.emit_object(node);
+                self.emit_object(node, false)?;
             }
         }
 
         // Write inline comment if present and requested
         if inline && emit_inline_comment {
             if let Some(ref comment) = node.inline_comment {
-                self.write_comment(comment, true);
+                self.write_comment(comment, true)?;
             }
         }
+        Ok(())
     }
 
-    fn emit_multiline_string(&mut self, s: &str) {
-        // Determine whether to use literal (|) or folded (>) style
-        // Use literal style if the string has meaningful line breaks
-        let has_trailing_newline = s.ends_with('\n');
-        let content = if has_trailing_newline {
-            &s[..s.len() - 1]
+    /// Write a string with line breaks as a literal block scalar, or quoted
+    /// when a block scalar can't hold it
+    fn emit_multiline_string(&mut self, s: &str) -> fmt::Result {
+        if self.emit_literal_string(s)? {
+            Ok(())
         } else {
-            s
+            self.write_quoted(s)
+        }
+    }
+
+    /// Write a string as a literal block scalar. Writes nothing and returns
+    /// `false` when the block wouldn't parse back as the same string.
+    fn emit_literal_string(&mut self, s: &str) -> Result<bool, fmt::Error> {
+        let Some((header, lines)) = literal_lines(s, self.options.indent) else {
+            return Ok(false);
+        };
+        self.write(&header)?;
+        // Write each line with proper indentation. The line break after
+        // the last one is left to whatever follows
+        self.current_indent += self.options.indent;
+        for line in lines {
+            self.newline()?;
+            if !line.is_empty() {
+                self.write_indent()?;
+                self.write(line)?;
+            }
+        }
+        self.current_indent -= self.options.indent;
+        self.open_block = header.ends_with('+');
+        Ok(true)
+    }
+
+    /// Write a long string as a folded block scalar wrapped at the line
+    /// width. Writes nothing and returns `false` when no width is set, the
+    /// string fits on the current line, or folding would change its value.
+    /// With `forced`, the string is folded whenever that keeps its value.
+    fn emit_folded_string(&mut self, node: &YamlNode, forced: bool) -> Result<bool, fmt::Error> {
+        let width = match self.options.line_width {
+            Some(width) => width,
+            None if forced => usize::MAX,
+            None => return Ok(false),
+        };
+        let YamlValue::String(s) = &node.value else {
+            return Ok(false);
+        };
+        // The comment would have to follow the indicator, where it's dropped
+        if self.options.comments && node.inline_comment.is_some() {
+            return Ok(false);
+        }
+        if !forced && self.column + s.chars().count() <= width {
+            return Ok(false);
+        }
+
+        // Clip keeps a single trailing newline, strip keeps none
+        let (body, chomp) = match s.strip_suffix('\n') {
+            Some(body) => (body, ">"),
+            None => (s.as_str(), ">-"),
         };
+        let Some(words) = fold_words(body) else {
+            return Ok(false);
+        };
+        let content_indent = self.current_indent + self.options.indent;
+        let lines = wrap_words(&words, width.saturating_sub(content_indent));
+        if lines.len() < 2 && !forced {
+            return Ok(false);
+        }
 
-        // Use literal style for strings with multiple lines
-        if content.contains('\n') {
-            // Literal style preserves line breaks
-            self.output.push('|');
-            if has_trailing_newline {
-                // Default clip mode - single trailing newline
-            } else {
-                // Strip mode - no trailing newline
-                self.output.push('-');
-            }
-            self.output.push('\n');
-
-            // Write each line with proper indentation
-            for line in content.lines() {
-                self.current_indent += self.indent_size;
-                self.write_indent();
-                self.output.push_str(line);
-                self.output.push('\n');
-                self.current_indent -= self.indent_size;
+        self.write(chomp)?;
+        self.current_indent = content_indent;
+        for line in lines {
+            self.newline()?;
+            self.write_indent()?;
+            self.write(&line)?;
+        }
+        self.current_indent -= self.options.indent;
+        Ok(true)
+    }
+
+    /// Write a string after `key: ` or `- ` in the style overrides ask for,
+    /// falling back to the usual choice, followed by its inline comment
+    fn emit_string(&mut self, node: &YamlNode, s: &str) -> fmt::Result {
+        let commented = self.options.comments && node.inline_comment.is_some();
+        let style = self.style_override();
+        let block = match style {
+            Some(ScalarStyle::Literal) if !commented => self.emit_literal_string(s)?,
+            Some(ScalarStyle::Folded) => self.emit_folded_string(node, true)?,
+            Some(ScalarStyle::Plain | ScalarStyle::Quoted) => false,
+            _ => {
+                self.emit_folded_string(node, false)?
+                    || (!commented && should_use_multiline(s) && self.emit_literal_string(s)?)
             }
+        };
+        if block {
+            return Ok(());
+        }
+
+        let text = self.scalar_text(s, style);
+        self.write(&text)?;
+        if let Some(ref comment) = node.inline_comment {
+            self.write_comment(comment, true)?;
+        }
+        Ok(())
+    }
+
+    /// Column where a value's inline comment goes when the value follows
+    /// `key:` or `-` ending at `column`, or `None` if it has no comment
+    /// written on that line
+    fn comment_start(&self, node: &YamlNode, column: usize, after_key: bool) -> Option<usize> {
+        if !self.options.comments || node.inline_comment.is_none() {
+            return None;
+        }
+        // ` &name !tag` goes between the indicator and the value
+        let column = column + properties(node).map_or(0, |p| p.chars().count() + 1);
+        // Values with comments are never written as block scalars
+        let text = match &node.value {
+            YamlValue::Blank => return Some(column),
+            YamlValue::String(s) => Some(self.scalar_text(s, self.style_override())),
+            YamlValue::Alias(name) => Some(Cow::Owned(format!("*{}", name))),
+            _ => self.flow_text(node, column + 1).map(Cow::Owned),
+        };
+        match text {
+            Some(text) => Some(column + 1 + text.chars().count()),
+            None if after_key && (node.is_array() || node.is_object()) => Some(column),
+            None => None,
+        }
+    }
+
+    fn key_text<'s>(&self, key: &'s str) -> Cow<'s, str> {
+        if self.needs_quotes(key) {
+            Cow::Owned(self.quoted(key))
         } else {
-            // For single long lines, could use folded style
-            // For now, just emit as quoted string
-            write!(&mut self.output, "\"{}\"", escape_string(s)).unwrap();
+            Cow::Borrowed(key)
         }
     }
 
-    fn emit_array(&mut self, items: &[YamlNode]) {
-        for (i, item) in items.iter().enumerate() {
-            if i > 0 {
-                self.output.push('\n');
-                self.write_indent();
+    fn emit_array(&mut self, items: &[YamlNode]) -> fmt::Result {
+        let outer_column = self.comment_column.take();
+        if self.options.align_comments {
+            for (i, item) in items.iter().enumerate() {
+                if self.tracks_path() {
+                    self.path.push(i.to_string());
+                }
+                let start = self.comment_start(item, self.current_indent + 1, false);
+                self.comment_column = self.comment_column.max(start.map(|start| start + 1));
+                self.path.pop();
             }
-            self.output.push_str("- ");
+        }
 
-            // Handle simple values
-            if let YamlValue::String(_) = &item.value {
-                self.emit_node(item, true);
-                continue;
+        self.blocks.push(self.current_indent);
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.newline()?;
             }
-
-            // Handle nested arrays
-            if let YamlValue::Array(_) = &item.value {
-                self.output.push('\n');
-                let old_indent = self.current_indent;
-                self.current_indent += self.indent_size;
-                self.write_indent();
-                self.emit_node(item, false);
-                self.current_indent = old_indent;
-                continue;
+            if self.tracks_path() {
+                self.path.push(i.to_string());
             }
+            self.emit_item(item)?;
+            self.path.pop();
+        }
+        self.end_block();
+        self.comment_column = outer_column;
+        Ok(())
+    }
 
-            // Handle objects in arrays
-            let YamlValue::Object(map) = &item.value else {
-                continue;
-            };
+    /// Leave the innermost block collection after its last entry
+    fn end_block(&mut self) {
+        if let Some(indent) = self.blocks.pop() {
+            self.closed.insert(0, indent);
+        }
+    }
 
-            let Some((first_key, first_value)) = map.iter().next() else {
-                continue;
-            };
+    fn emit_item(&mut self, item: &YamlNode) -> fmt::Result {
+        // Comments for an item go above its dash
+        self.write_leading_comment(item)?;
+        self.write_indent()?;
+        self.write("-")?;
+        self.write_properties(item)?;
 
-            // Write first key-value pair inline with the dash
-            if needs_quoting(first_key.as_ref()) {
-                write!(
-                    &mut self.output,
-                    "\"{}\"",
-                    escape_string(first_key.as_ref())
-                )
-                .unwrap();
-            } else {
-                self.output.push_str(first_key.as_ref());
-            }
-            self.output.push_str(": ");
-
-            // Emit first value
-            match &first_value.value {
-                YamlValue::Object(_) | YamlValue::Array(_) => {
-                    self.output.push('\n');
-                    let old_indent = self.current_indent;
-                    self.current_indent += self.indent_size * 2;
-                    self.write_indent();
-                    self.emit_node(first_value, false);
-                    self.current_indent = old_indent;
-                }
-                YamlValue::String(_) => {
-                    self.emit_node(first_value, true);
+        if self.emit_flow(item)? {
+            return Ok(());
+        }
+        match &item.value {
+            YamlValue::Blank => self.emit_empty(item)?,
+            YamlValue::String(s) => {
+                self.write(" ")?;
+                self.emit_string(item, s)?;
+            }
+            YamlValue::Alias(name) => {
+                self.write(" ")?;
+                self.emit_alias(item, name)?;
+            }
+            YamlValue::Array(nested) => {
+                // Nested sequences start on the next line, one level deeper
+                self.newline()?;
+                self.current_indent += self.options.indent;
+                self.emit_array(nested)?;
+                self.current_indent -= self.options.indent;
+            }
+            YamlValue::Object(map) => {
+                // The first pair shares the line with the dash, unless it
+                // has a comment that must go above it or the object has an
+                // anchor or tag, which would otherwise belong to the first
+                // value
+                let first_has_comment = self.options.comments
+                    && self
+                        .pairs(map)
+                        .first()
+                        .map_or(false, |(_, first)| first.leading_comment.is_some());
+                // Keys line up with the first one, just after the dash
+                self.current_indent += DASH_WIDTH;
+                if first_has_comment || properties(item).is_some() {
+                    self.newline()?;
+                    self.emit_object(item, false)?;
+                } else {
+                    self.write(" ")?;
+                    self.emit_object(item, true)?;
                 }
+                self.current_indent -= DASH_WIDTH;
             }
+        }
+        Ok(())
+    }
 
-            // Emit remaining properties
-            for (key, value) in map.iter().skip(1) {
-                self.output.push('\n');
-                // Indent for array item properties
-                for _ in 0..(self.current_indent + self.indent_size) {
-                    self.output.push(' ');
-                }
+    /// The pairs of an object in output order: sorted by key in canonical
+    /// mode, as inserted otherwise. A mapping keeps its order if sorting
+    /// would change which anchor an alias refers to.
+    fn pairs<'a>(&self, map: &'a YamlObject) -> Vec<(&'a String, &'a YamlNode)> {
+        let pairs: Vec<_> = map.iter().collect();
+        if !self.options.canonical {
+            return pairs;
+        }
+        let mut order: Vec<usize> = (0..pairs.len()).collect();
+        order.sort_by(|&a, &b| pairs[a].0.cmp(pairs[b].0));
 
-                if needs_quoting(key.as_ref()) {
-                    write!(&mut self.output, "\"{}\"", escape_string(key.as_ref())).unwrap();
-                } else {
-                    self.output.push_str(key.as_ref());
-                }
-                self.output.push_str(": ");
-
-                match &value.value {
-                    YamlValue::Object(_) | YamlValue::Array(_) => {
-                        self.output.push('\n');
-                        let old_indent = self.current_indent;
-                        self.current_indent += self.indent_size * 2;
-                        self.write_indent();
-                        self.emit_node(value, false);
-                        self.current_indent = old_indent;
-                    }
-                    YamlValue::String(_) => {
-                        self.emit_node(value, true);
-                    }
+        let names: Vec<_> = pairs.iter().map(|(_, value)| anchor_names(value)).collect();
+        for (position, &written) in order.iter().enumerate() {
+            for &later in &order[position + 1..] {
+                // `later` came first in the mapping but is now written after
+                if later < written && names_depend(&names[later], &names[written]) {
+                    return pairs;
                 }
             }
         }
+        order.into_iter().map(|index| pairs[index]).collect()
     }
 
-    fn emit_object(&mut self, node: &YamlNode) {
+    /// Emit the pairs of an object, one per line at the current indent.
+    /// With `after_dash`, the first key continues a line already started by
+    /// a sequence dash.
+    fn emit_object(&mut self, node: &YamlNode, after_dash: bool) -> fmt::Result {
         let YamlValue::Object(map) = &node.value else {
-            return;
+            return Ok(());
+        };
+
+        let pairs = self.pairs(map);
+
+        // Keys are padded to the widest one so string values line up
+        let key_width = if self.options.align_values {
+            pairs
+                .iter()
+                .filter(|(_, value)| value.is_string())
+                .map(|(key, _)| self.key_text(key).chars().count())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
         };
 
-        let mut first = true;
-        for (key, value) in map.iter() {
-            if !first {
-                self.output.push('\n');
-            } else {
-                first = false;
+        let outer_column = self.comment_column.take();
+        if self.options.align_comments {
+            for &(key, value) in &pairs {
+                if self.tracks_path() {
+                    self.path.push(key.clone());
+                }
+                let mut column = self.current_indent + self.key_text(key).chars().count() + 1;
+                if value.is_string() {
+                    column = column.max(self.current_indent + key_width + 1);
+                }
+                let start = self.comment_start(value, column, true);
+                self.comment_column = self.comment_column.max(start.map(|start| start + 1));
+                self.path.pop();
             }
+        }
 
-            // Write leading comment for this key-value pair if present
-            if let Some(ref comment) = value.leading_comment {
-                self.write_comment(comment, false);
+        self.blocks.push(self.current_indent);
+        for (i, (key, value)) in pairs.into_iter().enumerate() {
+            if i > 0 {
+                self.newline()?;
             }
+            if self.tracks_path() {
+                self.path.push(key.clone());
+            }
+            self.emit_pair(key, value, after_dash && i == 0, key_width)?;
+            self.path.pop();
+        }
+        self.end_block();
+        self.comment_column = outer_column;
+        Ok(())
+    }
+
+    /// Write a pair, padding the key to `key_width` if its value is a string
+    fn emit_pair(
+        &mut self,
+        key: &str,
+        value: &YamlNode,
+        continues_line: bool,
+        key_width: usize,
+    ) -> fmt::Result {
+        if continues_line {
+            self.closed.clear();
+        } else {
+            // Write leading comment for this key-value pair if present
+            self.write_leading_comment(value)?;
 
             // Always write indent for the key (comment function handles its own indentation)
-            self.write_indent();
-
-            // Write key
-            if needs_quoting(key.as_ref()) {
-                write!(&mut self.output, "\"{}\"", escape_string(key.as_ref())).unwrap();
-            } else {
-                self.output.push_str(key.as_ref());
-            }
-            self.output.push(':');
-
-            // Check if value is complex
-            match &value.value {
-                YamlValue::Object(_) | YamlValue::Array(_) => {
-                    // Write inline comment for key if present
-                    if let Some(ref comment) = value.inline_comment {
-                        self.output.push(' ');
-                        self.write_comment(comment, true);
-                    }
-
-                    self.output.push('\n');
-                    let old_indent = self.current_indent;
-                    self.current_indent += self.indent_size;
-                    // Don't emit leading comment again - it was already emitted above
-                    self.emit_node_with_comment_control(value, false, false);
-                    self.current_indent = old_indent;
+            self.write_indent()?;
+        }
+
+        // Write key
+        let key = self.key_text(key);
+        self.write(&key)?;
+        self.write(":")?;
+
+        // Check if value is complex
+        match &value.value {
+            YamlValue::Object(_) | YamlValue::Array(_) => {
+                self.write_properties(value)?;
+                if self.emit_flow(value)? {
+                    // Written on the key's line
+                    return Ok(());
                 }
-                YamlValue::String(s) => {
-                    // Check if string should be multiline
-                    if should_use_multiline(s.as_ref()) {
-                        self.output.push(' '); // Space after colon
-                        self.emit_multiline_string(s.as_ref());
-                    } else {
-                        self.output.push(' ');
-                        self.emit_node(value, true);
-                    }
+
+                // Write inline comment for key if present
+                if let Some(ref comment) = value.inline_comment {
+                    self.write_comment(comment, true)?;
                 }
+
+                self.newline()?;
+                let old_indent = self.current_indent;
+                let flush =
+                    value.is_array() && self.options.sequence_indent == SequenceIndent::Flush;
+                if !flush {
+                    self.current_indent += self.options.indent;
+                }
+                // Don't emit leading comment again - it was already emitted above
+                self.emit_node_with_comment_control(value, false, false)?;
+                self.current_indent = old_indent;
+            }
+            YamlValue::Blank => {
+                self.write_properties(value)?;
+                self.emit_empty(value)?;
+            }
+            YamlValue::String(s) => {
+                let padding = key_width.saturating_sub(key.chars().count());
+                self.write(&" ".repeat(padding))?;
+                self.write_properties(value)?;
+                self.write(" ")?;
+                self.emit_string(value, s)?;
+            }
+            YamlValue::Alias(name) => {
+                self.write(" ")?;
+                self.emit_alias(value, name)?;
             }
         }
+        Ok(())
+    }
+}
+
+/// `&anchor !tag`, as written before a node, if it has either
+fn properties(node: &YamlNode) -> Option<String> {
+    let anchor = node.anchor.as_ref().map(|anchor| format!("&{}", anchor));
+    let properties: Vec<String> = anchor.into_iter().chain(node.tag.clone()).collect();
+    (!properties.is_empty()).then(|| properties.join(" "))
+}
+
+/// `[]` or `{}`, which have no block form
+fn is_empty_collection(node: &YamlNode) -> bool {
+    match &node.value {
+        YamlValue::Array(items) => items.is_empty(),
+        YamlValue::Object(map) => map.is_empty(),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => false,
     }
 }
 
 fn should_use_multiline(s: &str) -> bool {
-    // Use multiline if string contains newlines
     s.contains('\n')
 }
 
-fn needs_quoting(s: &str) -> bool {
+/// Split a single-line string into words at the spaces where a folded
+/// block scalar may break it, or `None` if it can't be folded unchanged.
+///
+/// A break is only made at a single space followed by a letter or digit, so
+/// no line starts with whitespace or a character with a meaning of its own.
+fn fold_words(s: &str) -> Option<Vec<&str>> {
+    if !s.starts_with(char::is_alphanumeric)
+        || s.ends_with(char::is_whitespace)
+        || s.chars().any(char::is_control)
+    {
+        return None;
+    }
+
+    let mut words = Vec::new();
+    let mut word_start = 0;
+    let mut prev = None;
+    let mut chars = s.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let breakable = c == ' '
+            && prev.is_some_and(|prev: char| !prev.is_whitespace())
+            && next.is_some_and(char::is_alphanumeric);
+        if breakable {
+            words.push(&s[word_start..index]);
+            word_start = index + 1;
+        }
+        prev = Some(c);
+    }
+    words.push(&s[word_start..]);
+
+    Some(words)
+}
+
+/// Greedily join words into lines of at most `width` characters. A word
+/// longer than the width gets a line of its own.
+fn wrap_words(words: &[&str], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in words {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+/// The header and lines of `s` as a literal block scalar indented by
+/// `indent`, if one can hold it
+fn literal_lines(s: &str, indent: usize) -> Option<(String, std::str::Split<'_, char>)> {
     if s.is_empty() {
-        return true;
-    }
-
-    // Check for special YAML values that need quoting
-    matches!(s, "true" | "false" | "null")
-        || s.chars().any(|c| matches!(c, ':' | '#' | '[' | ']' | '{' | '}' | ',' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%' | '@' | '`' | '~'))
-        || s.starts_with(' ')
-        || s.ends_with(' ')
-        || s.starts_with('-')
-        || s.parse::<f64>().is_ok()
-        // Quote leading zeros to preserve them
-        || (s.len() > 1 && s.starts_with('0') && s.chars().nth(1).is_some_and(|c| c.is_ascii_digit()))
+        return None;
+    }
+    let body = s.trim_end_matches('\n');
+    // Leading whitespace on the first line needs an indentation indicator
+    let indicator = if body.starts_with([' ', '\t']) {
+        match indent {
+            1..=9 => indent.to_string(),
+            _ => return None,
+        }
+    } else {
+        String::new()
+    };
+    if body
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\t'))
+    {
+        return None;
+    }
+    // Lines of only whitespace read back as empty ones
+    if body
+        .split('\n')
+        .any(|line| !line.is_empty() && line.trim().is_empty())
+    {
+        return None;
+    }
+    // Clip keeps one trailing newline, and only when there is content
+    let chomp = match s.len() - body.len() {
+        0 => "-",
+        1 if !body.is_empty() => "",
+        _ => "+",
+    };
+    let header = format!("|{}{}", indicator, chomp);
+    Some((header, s.strip_suffix('\n').unwrap_or(s).split('\n')))
 }
 
 fn escape_string(s: &str) -> String {
@@ -352,12 +1070,44 @@ fn escape_string(s: &str) -> String {
     result
 }
 
-impl Default for Emitter {
+impl Default for Emitter<String> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// The anchors defined and the aliases used in a tree
+#[derive(Default)]
+struct AnchorNames<'a> {
+    anchors: Vec<&'a str>,
+    aliases: Vec<&'a str>,
+}
+
+fn anchor_names(node: &YamlNode) -> AnchorNames<'_> {
+    fn collect<'a>(node: &'a YamlNode, names: &mut AnchorNames<'a>) {
+        if let Some(anchor) = &node.anchor {
+            names.anchors.push(anchor);
+        }
+        match &node.value {
+            YamlValue::Alias(name) => names.aliases.push(name),
+            YamlValue::Array(items) => items.iter().for_each(|item| collect(item, names)),
+            YamlValue::Object(map) => map.values().for_each(|value| collect(value, names)),
+            YamlValue::String(_) | YamlValue::Blank => {}
+        }
+    }
+    let mut names = AnchorNames::default();
+    collect(node, &mut names);
+    names
+}
+
+/// Whether swapping two trees could change what an alias refers to
+fn names_depend(a: &AnchorNames, b: &AnchorNames) -> bool {
+    a.anchors
+        .iter()
+        .any(|name| b.anchors.contains(name) || b.aliases.contains(name))
+        || b.anchors.iter().any(|name| a.aliases.contains(name))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
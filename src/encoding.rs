@@ -0,0 +1,124 @@
+//! Character encodings YAML files may use, detected as the YAML spec
+//! describes: from a byte order mark, or else from the pattern of zero bytes
+//! around the first character, which is always ASCII in a YAML stream.
+
+/// Byte order mark, as a character at the start of decoded text
+pub(crate) const BOM: char = '\u{FEFF}';
+
+/// Encoding of a YAML file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding {
+    /// The encoding of `bytes`, and whether they start with a byte order mark.
+    ///
+    /// ```rust
+    /// use yamp::Encoding;
+    ///
+    /// assert_eq!(Encoding::detect(b"\xFF\xFEa\x00"), (Encoding::Utf16Le, true));
+    /// assert_eq!(Encoding::detect(b"\x00a\x00:"), (Encoding::Utf16Be, false));
+    /// assert_eq!(Encoding::detect(b"a: 1"), (Encoding::Utf8, false));
+    /// ```
+    pub fn detect(bytes: &[u8]) -> (Self, bool) {
+        match bytes {
+            [0x00, 0x00, 0xFE, 0xFF, ..] => (Encoding::Utf32Be, true),
+            [0x00, 0x00, 0x00, _, ..] => (Encoding::Utf32Be, false),
+            [0xFF, 0xFE, 0x00, 0x00, ..] => (Encoding::Utf32Le, true),
+            [_, 0x00, 0x00, 0x00, ..] => (Encoding::Utf32Le, false),
+            [0xFE, 0xFF, ..] => (Encoding::Utf16Be, true),
+            [0x00, _, ..] => (Encoding::Utf16Be, false),
+            [0xFF, 0xFE, ..] => (Encoding::Utf16Le, true),
+            [_, 0x00, ..] => (Encoding::Utf16Le, false),
+            [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, true),
+            _ => (Encoding::Utf8, false),
+        }
+    }
+
+    /// Decode `bytes`, keeping a byte order mark as a leading `U+FEFF`.
+    pub fn decode(self, bytes: &[u8]) -> Result<String, String> {
+        match self {
+            Encoding::Utf8 => String::from_utf8(bytes.to_vec())
+                .map_err(|e| format!("Invalid UTF-8 at byte {}", e.utf8_error().valid_up_to())),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let units = units::<2>(bytes, self)?.map(|unit| unit as u16);
+                let mut text = String::with_capacity(bytes.len() / 2);
+                let mut offset = 0;
+                for c in char::decode_utf16(units) {
+                    let c = c.map_err(|_| format!("Invalid UTF-16 at byte {}", offset))?;
+                    offset += c.len_utf16() * 2;
+                    text.push(c);
+                }
+                Ok(text)
+            }
+            Encoding::Utf32Le | Encoding::Utf32Be => units::<4>(bytes, self)?
+                .enumerate()
+                .map(|(index, unit)| {
+                    char::from_u32(unit)
+                        .ok_or_else(|| format!("Invalid UTF-32 at byte {}", index * 4))
+                })
+                .collect(),
+        }
+    }
+
+    /// Encode `text`, starting with a byte order mark when `bom` is set.
+    pub fn encode(self, text: &str, bom: bool) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(text.len() + 4);
+        let chars = bom.then_some(BOM).into_iter().chain(text.chars());
+        match self {
+            Encoding::Utf8 => {
+                for c in chars {
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                for c in chars {
+                    for &unit in c.encode_utf16(&mut [0; 2]).iter() {
+                        bytes.extend_from_slice(&if self == Encoding::Utf16Le {
+                            unit.to_le_bytes()
+                        } else {
+                            unit.to_be_bytes()
+                        });
+                    }
+                }
+            }
+            Encoding::Utf32Le | Encoding::Utf32Be => {
+                for c in chars {
+                    let unit = u32::from(c);
+                    bytes.extend_from_slice(&if self == Encoding::Utf32Le {
+                        unit.to_le_bytes()
+                    } else {
+                        unit.to_be_bytes()
+                    });
+                }
+            }
+        }
+        bytes
+    }
+}
+
+/// The code units of `bytes`, `N` bytes wide in the byte order of `encoding`
+fn units<const N: usize>(
+    bytes: &[u8],
+    encoding: Encoding,
+) -> Result<impl Iterator<Item = u32> + '_, String> {
+    if bytes.len() % N != 0 {
+        return Err(format!(
+            "Input length {} is not a multiple of {} for {:?}",
+            bytes.len(),
+            N,
+            encoding
+        ));
+    }
+    let little_endian = matches!(encoding, Encoding::Utf16Le | Encoding::Utf32Le);
+    Ok(bytes.chunks_exact(N).map(move |chunk| {
+        let ordered = |i: usize| u32::from(chunk[if little_endian { N - 1 - i } else { i }]);
+        (0..N).fold(0, |unit, i| unit << 8 | ordered(i))
+    }))
+}
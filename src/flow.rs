@@ -0,0 +1,402 @@
+//! Flow collections: `[a, b]` and `{k: v}`, possibly nested and spread
+//! over several lines.
+
+use crate::anchors::{self, Anchors};
+use crate::lexer;
+use crate::types::{CollectionStyle, YamlNode, YamlObject, YamlValue};
+
+/// Parse the text of a flow collection, as read by the lexer, from its
+/// opening bracket to the matching closing one.
+///
+/// Comments on their own lines go to the entry after them, and a comment
+/// after an entry on the same line is that entry's inline comment.
+pub(crate) fn parse_flow(text: &str, anchors: &mut Anchors) -> Result<YamlNode, String> {
+    let mut parser = FlowParser {
+        text,
+        position: 0,
+        anchors,
+    };
+    let node = parser.node()?;
+    parser.comments();
+    match parser.peek() {
+        None => Ok(node),
+        Some(c) => Err(format!("Unexpected '{}' after flow collection", c)),
+    }
+}
+
+/// Comments found between two entries
+#[derive(Default)]
+struct Comments {
+    /// On the same line as the entry before
+    inline: Option<String>,
+    /// On lines of their own
+    leading: Vec<String>,
+}
+
+impl Comments {
+    fn all(self) -> Vec<String> {
+        self.inline.into_iter().chain(self.leading).collect()
+    }
+
+    fn merge(&mut self, other: Comments) {
+        if self.inline.is_none() {
+            self.inline = other.inline;
+        } else {
+            self.leading.extend(other.inline);
+        }
+        self.leading.extend(other.leading);
+    }
+}
+
+fn join(comments: Vec<String>) -> Option<String> {
+    (!comments.is_empty()).then(|| comments.join("\n"))
+}
+
+struct FlowParser<'g, 'a> {
+    text: &'g str,
+    position: usize,
+    anchors: &'a mut Anchors,
+}
+
+impl<'g> FlowParser<'g, '_> {
+    fn rest(&self) -> &'g str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += c.len_utf8();
+        }
+        found
+    }
+
+    /// Skip whitespace, line breaks and comments
+    fn comments(&mut self) -> Comments {
+        let mut comments = Comments::default();
+        let mut new_line = false;
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' => self.position += 1,
+                '\n' => {
+                    new_line = true;
+                    self.position += 1;
+                }
+                '#' => {
+                    let rest = self.rest();
+                    let end = rest.find('\n').unwrap_or(rest.len());
+                    let comment = rest[..end].trim_start_matches('#').trim().to_string();
+                    self.position += end;
+                    if new_line || comments.inline.is_some() {
+                        comments.leading.push(comment);
+                    } else {
+                        comments.inline = Some(comment);
+                    }
+                }
+                _ => break,
+            }
+        }
+        comments
+    }
+
+    fn node(&mut self) -> Result<YamlNode, String> {
+        if let Some(name) = self.name_after('&') {
+            self.anchors.open(name)?;
+            self.comments();
+            let mut node = self.node()?;
+            self.anchors.close(&mut node);
+            return Ok(node);
+        }
+        if let Some(name) = self.name_after('*') {
+            return self.anchors.resolve(name);
+        }
+        if self.peek() == Some('!') {
+            let length = lexer::tag_len(self.rest());
+            let tag = self.rest()[..length].to_string();
+            self.position += length;
+            self.comments();
+            // A tag with nothing after it is on a blank node
+            let mut node = match self.peek() {
+                None | Some(',' | ']' | '}') => YamlNode::blank(),
+                Some(_) => self.node()?,
+            };
+            node.tag = Some(tag);
+            return Ok(node);
+        }
+        match self.peek() {
+            Some('[') => self.sequence(),
+            Some('{') => self.mapping(),
+            _ => Ok(YamlNode::from_value(YamlValue::String(self.scalar()?))),
+        }
+    }
+
+    /// The name of an anchor or alias starting with `indicator`, consumed
+    fn name_after(&mut self, indicator: char) -> Option<&'g str> {
+        let name = self.rest().strip_prefix(indicator)?;
+        let length = name
+            .find(|c| !anchors::is_name_char(c))
+            .unwrap_or(name.len());
+        if length == 0 {
+            return None;
+        }
+        self.position += indicator.len_utf8() + length;
+        Some(&name[..length])
+    }
+
+    /// Whether an anchor, alias or tag starts here
+    fn at_property(&self) -> bool {
+        let mut chars = self.rest().chars();
+        match chars.next() {
+            Some('!') => true,
+            Some('&' | '*') => chars.next().is_some_and(anchors::is_name_char),
+            _ => false,
+        }
+    }
+
+    fn sequence(&mut self) -> Result<YamlNode, String> {
+        self.eat('[');
+        let mut items: Vec<YamlNode> = Vec::new();
+        let mut comments = self.comments();
+        let mut leading = comments.all();
+
+        while !self.eat(']') {
+            if self.peek().is_none() {
+                return Err("Unterminated flow sequence".to_string());
+            }
+            let mut item = match self.peek() {
+                Some('[' | '{') => self.node()?,
+                _ if self.at_property() => self.node()?,
+                _ => {
+                    let scalar = self.scalar()?;
+                    // `[key: value]` is a mapping with a single pair
+                    if self.eat_pair_colon() {
+                        let mut pair = YamlObject::new();
+                        pair.insert(scalar, self.pair_value(']')?);
+                        YamlNode::from_value(YamlValue::Object(pair))
+                            .with_style(CollectionStyle::Flow)
+                    } else {
+                        YamlNode::from_value(YamlValue::String(scalar))
+                    }
+                }
+            };
+
+            comments = self.separator(']', "sequence")?;
+            prepend(&mut item, std::mem::take(&mut leading));
+            item.inline_comment = comments.inline;
+            leading = comments.leading;
+            items.push(item);
+        }
+        attach_trailing(items.last_mut(), leading);
+
+        Ok(YamlNode::from_value(YamlValue::Array(items)).with_style(CollectionStyle::Flow))
+    }
+
+    fn mapping(&mut self) -> Result<YamlNode, String> {
+        self.eat('{');
+        let mut object = YamlObject::new();
+        let mut last_key = None;
+        let comments = self.comments();
+        let mut leading = comments.all();
+
+        while !self.eat('}') {
+            match self.peek() {
+                None => return Err("Unterminated flow mapping".to_string()),
+                Some('[' | '{') => {
+                    return Err("Collections as mapping keys are not supported".to_string())
+                }
+                Some(_) => {}
+            }
+            let key = self.scalar()?;
+            // `{key}` and `{key:}` have a blank value
+            let mut value = if self.eat_pair_colon() {
+                self.pair_value('}')?
+            } else {
+                YamlNode::blank()
+            };
+
+            let comments = self.separator('}', "mapping")?;
+            prepend(&mut value, std::mem::take(&mut leading));
+            value.inline_comment = comments.inline;
+            leading = comments.leading;
+            object.insert(key.clone(), value);
+            last_key = Some(key);
+        }
+        attach_trailing(last_key.and_then(|key| object.get_mut(&key)), leading);
+
+        Ok(YamlNode::from_value(YamlValue::Object(object)).with_style(CollectionStyle::Flow))
+    }
+
+    /// Consume the `:` after a key, if there is one
+    fn eat_pair_colon(&mut self) -> bool {
+        let start = self.position;
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.position += 1;
+        }
+        let found = self.eat(':');
+        if !found {
+            self.position = start;
+        }
+        found
+    }
+
+    /// The value after `key:`, blank when the entry ends right away
+    fn pair_value(&mut self, close: char) -> Result<YamlNode, String> {
+        let comments = self.comments();
+        let mut value = match self.peek() {
+            Some(c) if c == ',' || c == close => YamlNode::blank(),
+            _ => self.node()?,
+        };
+        value.leading_comment = join(comments.all());
+        Ok(value)
+    }
+
+    /// Comments up to the next entry, past the `,` ending this one
+    fn separator(&mut self, close: char, kind: &str) -> Result<Comments, String> {
+        let mut comments = self.comments();
+        if self.eat(',') {
+            comments.merge(self.comments());
+        } else {
+            match self.peek() {
+                Some(c) if c == close => {}
+                Some(c) => {
+                    return Err(format!(
+                        "Expected ',' or '{}' in flow {}, found '{}'",
+                        close, kind, c
+                    ))
+                }
+                None => return Err(format!("Unterminated flow {}", kind)),
+            }
+        }
+        Ok(comments)
+    }
+
+    fn scalar(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.quoted(quote),
+            Some(c @ (',' | ']' | '}')) => Err(format!("Expected a value, found '{}'", c)),
+            None => Err("Unexpected end of flow collection".to_string()),
+            Some(_) => Ok(self.plain()),
+        }
+    }
+
+    /// A quoted string without its quotes, as in block context
+    fn quoted(&mut self, quote: char) -> Result<String, String> {
+        let rest = self.rest();
+        let mut chars = rest.char_indices().skip(1);
+        while let Some((index, c)) = chars.next() {
+            if c == '\\' && quote == '"' {
+                chars.next();
+            } else if c == quote {
+                // `''` is an escaped quote in a single-quoted string
+                if quote == '\'' && rest[index + 1..].starts_with('\'') {
+                    chars.next();
+                    continue;
+                }
+                self.position += index + 1;
+                return Ok(fold_quoted(&rest[1..index], quote));
+            }
+        }
+        Err("Unterminated quoted string in flow collection".to_string())
+    }
+
+    /// A plain scalar, which ends at a flow indicator, a `: ` or a comment.
+    /// Line breaks in it fold into spaces.
+    fn plain(&mut self) -> String {
+        let rest = self.rest();
+        let mut end = rest.len();
+        let mut previous = ' ';
+        for (index, c) in rest.char_indices() {
+            let next = rest[index + c.len_utf8()..].chars().next();
+            let ends_value = match c {
+                ',' | '[' | ']' | '{' | '}' => true,
+                ':' => next.map_or(true, |next| next.is_whitespace() || ",[]{}".contains(next)),
+                '#' => previous.is_whitespace(),
+                _ => false,
+            };
+            if ends_value && index > 0 {
+                end = index;
+                break;
+            }
+            previous = c;
+        }
+        let text = &rest[..end];
+        let trimmed = text.trim_end();
+        self.position += trimmed.len();
+        fold_lines(trimmed)
+    }
+}
+
+/// Join the lines of a multi-line scalar: a single line break becomes a
+/// space, and each further one in a row is kept.
+pub(crate) fn fold_lines(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    let mut breaks = 0;
+    for (index, line) in text.split('\n').map(str::trim).enumerate() {
+        if index > 0 && line.is_empty() {
+            breaks += 1;
+            continue;
+        }
+        if index > 0 {
+            if breaks == 0 {
+                folded.push(' ');
+            }
+            folded.extend(std::iter::repeat('\n').take(breaks));
+        }
+        folded.push_str(line);
+        breaks = 0;
+    }
+    folded
+}
+
+/// Join the lines of a quoted scalar's content. Line breaks fold as in
+/// [`fold_lines`], keeping the whitespace at either end of the string, and
+/// in a double-quoted string a `\` ending a line joins it to the next.
+pub(crate) fn fold_quoted(content: &str, quote: char) -> String {
+    if !content.contains('\n') {
+        return content.to_string();
+    }
+    let last = content.matches('\n').count();
+    let mut folded = String::with_capacity(content.len());
+    let mut breaks = 0;
+    let mut joined = false;
+    for (index, line) in content.split('\n').enumerate() {
+        let line = if index > 0 { line.trim_start() } else { line };
+        let mut line = if index < last { line.trim_end() } else { line };
+        if index > 0 && index < last && line.is_empty() {
+            breaks += 1;
+            continue;
+        }
+        if index > 0 {
+            if breaks == 0 && !joined {
+                folded.push(' ');
+            }
+            folded.extend(std::iter::repeat('\n').take(breaks));
+        }
+        breaks = 0;
+        let backslashes = line.len() - line.trim_end_matches('\\').len();
+        joined = quote == '"' && index < last && backslashes % 2 == 1;
+        if joined {
+            line = &line[..line.len() - 1];
+        }
+        folded.push_str(line);
+    }
+    folded
+}
+
+/// Comments before an entry, ahead of any it already has
+fn prepend(node: &mut YamlNode, comments: Vec<String>) {
+    let all = comments.into_iter().chain(node.leading_comment.take());
+    node.leading_comment = join(all.collect());
+}
+
+/// Comments after the last entry, before the closing bracket
+fn attach_trailing(node: Option<&mut YamlNode>, comments: Vec<String>) {
+    if let Some(node) = node {
+        let all = node.inline_comment.take().into_iter().chain(comments);
+        node.inline_comment = join(all.collect());
+    }
+}
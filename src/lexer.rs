@@ -1,4 +1,5 @@
-use std::{iter::Peekable, str::CharIndices};
+use crate::anchors;
+use std::{borrow::Cow, iter::Peekable, str::CharIndices};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum TokenKind {
@@ -13,6 +14,10 @@ pub(crate) enum TokenKind {
     Dedent,
     Pipe,        // | for literal multiline
     GreaterThan, // > for folded multiline
+    Flow,        // [...] or {...}, up to the matching bracket
+    Anchor,      // &name
+    Alias,       // *name
+    Tag,         // !name, !!name or !<verbatim>
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -41,6 +46,14 @@ pub(crate) struct Lexer<'g> {
     line: usize,
     column: usize,
     indent_stack: Vec<usize>,
+    /// Column of the node a block scalar indicator on this line belongs to:
+    /// the first token of the line, or the first one after the last `-`
+    node_column: Option<usize>,
+    after_hyphen: bool,
+    /// Set by `|` or `>`; the following lines are read as block scalar content
+    block_scalar_column: Option<usize>,
+    /// The indentation indicator of the last block scalar header, as in `|2`
+    block_scalar_indent: Option<usize>,
 }
 
 impl<'g> Lexer<'g> {
@@ -52,6 +65,10 @@ impl<'g> Lexer<'g> {
             line: 1,
             column: 1,
             indent_stack: vec![0],
+            node_column: None,
+            after_hyphen: false,
+            block_scalar_column: None,
+            block_scalar_indent: None,
         }
     }
     pub(crate) fn tokenize(&mut self) -> Vec<Token<'g>> {
@@ -63,6 +80,7 @@ impl<'g> Lexer<'g> {
         while let Some((start, c)) = self.chars.next() {
             let start_line = self.line;
             let start_column = self.column;
+            let token_count = tokens.len();
 
             match c {
                 '\n' => {
@@ -76,6 +94,11 @@ impl<'g> Lexer<'g> {
                     self.column = 1;
                     at_line_start = true;
                     self.current = start + 1;
+                    self.node_column = None;
+                    self.after_hyphen = false;
+                    if let Some(node_column) = self.block_scalar_column.take() {
+                        self.consume_block_scalar(&mut tokens, node_column);
+                    }
                 }
                 ' ' | '\t' if at_line_start => {
                     let (indent_level, end) = self.consume_indent(start);
@@ -113,10 +136,7 @@ impl<'g> Lexer<'g> {
                     self.column += end - start;
                     at_line_start = false;
                 }
-                '-' if self.peek_char() == Some(' ')
-                    || self.peek_char() == Some('\t')
-                    || self.peek_char() == Some('\n') =>
-                {
+                '-' if matches!(self.peek_char(), None | Some(' ' | '\t' | '\n')) => {
                     tokens.push(Token::new(TokenKind::Hyphen, "-", start_line, start_column));
                     self.current += 1;
                     self.column += 1;
@@ -149,8 +169,8 @@ impl<'g> Lexer<'g> {
                         start_line,
                         start_column,
                     ));
+                    self.move_past(&self.source[start..end]);
                     self.current = end;
-                    self.column += end - start;
                     at_line_start = false;
                 }
                 '0'..='9' => {
@@ -216,26 +236,73 @@ impl<'g> Lexer<'g> {
                     self.column += end - start;
                     at_line_start = false;
                 }
-                '|' => {
+                '|' | '>' => {
+                    let end = self.consume_block_header(start);
+                    let kind = if c == '|' {
+                        TokenKind::Pipe
+                    } else {
+                        TokenKind::GreaterThan
+                    };
+                    let header = &self.source[start..end];
+                    self.block_scalar_indent = indentation_indicator(header);
+                    tokens.push(Token::new(kind, header, start_line, start_column));
+                    self.current = end;
+                    self.column += end - start;
+                    at_line_start = false;
+                }
+                '&' | '*' if self.peek_char().is_some_and(anchors::is_name_char) => {
+                    let end = self.consume_name(start);
+                    let kind = if c == '&' {
+                        TokenKind::Anchor
+                    } else {
+                        TokenKind::Alias
+                    };
                     tokens.push(Token::new(
-                        TokenKind::Pipe,
-                        &self.source[start..start + 1],
+                        kind,
+                        &self.source[start..end],
                         start_line,
                         start_column,
                     ));
-                    self.current = start + 1;
-                    self.column += 1;
+                    self.current = end;
+                    self.column += end - start;
                     at_line_start = false;
                 }
-                '>' => {
+                '!' => {
+                    let end = self.consume_tag(start);
                     tokens.push(Token::new(
-                        TokenKind::GreaterThan,
-                        &self.source[start..start + 1],
+                        TokenKind::Tag,
+                        &self.source[start..end],
                         start_line,
                         start_column,
                     ));
-                    self.current = start + 1;
-                    self.column += 1;
+                    self.current = end;
+                    self.column += end - start;
+                    at_line_start = false;
+                }
+                '[' | '{' => {
+                    let end = self.consume_flow(start);
+                    tokens.push(Token::new(
+                        TokenKind::Flow,
+                        &self.source[start..end],
+                        start_line,
+                        start_column,
+                    ));
+                    self.move_past(&self.source[start..end]);
+                    self.current = end;
+                    at_line_start = false;
+                }
+                c if !is_indicator(c) && !c.is_control() => {
+                    // Any other character (`/`, `$`, non-ASCII letters, ...)
+                    // starts a plain value as well
+                    let end = self.consume_simple_value(start);
+                    tokens.push(Token::new(
+                        TokenKind::Identifier,
+                        &self.source[start..end],
+                        start_line,
+                        start_column,
+                    ));
+                    self.current = end;
+                    self.column += end - start;
                     at_line_start = false;
                 }
                 _other_char => {
@@ -243,6 +310,10 @@ impl<'g> Lexer<'g> {
                     self.column += 1;
                 }
             }
+
+            if let Some(token) = tokens.get(token_count) {
+                self.track_node(token.kind.clone(), token.column);
+            }
         }
 
         // Handle remaining dedents at end of file
@@ -254,6 +325,112 @@ impl<'g> Lexer<'g> {
         tokens
     }
 
+    fn track_node(&mut self, kind: TokenKind, column: usize) {
+        match kind {
+            TokenKind::Hyphen => {
+                self.node_column = Some(column);
+                self.after_hyphen = true;
+            }
+            TokenKind::Identifier | TokenKind::String | TokenKind::Flow | TokenKind::Alias => {
+                if self.node_column.is_none() || self.after_hyphen {
+                    self.node_column = Some(column);
+                    self.after_hyphen = false;
+                }
+            }
+            TokenKind::Pipe | TokenKind::GreaterThan => {
+                self.block_scalar_column = Some(self.node_column.unwrap_or(0));
+            }
+            TokenKind::Colon
+            | TokenKind::Whitespace
+            | TokenKind::NewLine
+            | TokenKind::Comment
+            | TokenKind::Indent
+            | TokenKind::Dedent
+            | TokenKind::Anchor
+            | TokenKind::Tag => {}
+        }
+    }
+
+    /// Read the indentation and chomping indicators after `|` or `>`, in
+    /// either order
+    fn consume_block_header(&mut self, start: usize) -> usize {
+        let mut end = start + 1;
+        let (mut indent, mut chomp) = (false, false);
+        while let Some((index, c)) = self.chars.next_if(|&(_, c)| {
+            (!indent && matches!(c, '1'..='9')) || (!chomp && matches!(c, '-' | '+'))
+        }) {
+            indent |= c.is_ascii_digit();
+            chomp |= !c.is_ascii_digit();
+            end = index + 1;
+        }
+        end
+    }
+
+    /// Read the lines of a block scalar as raw text, one `Identifier` per
+    /// line, instead of tokenizing them. Content lines are those indented
+    /// past `node_column`; blank lines in between become bare newlines.
+    ///
+    /// Text is taken from the content indentation, given by the header's
+    /// indentation indicator or set by the first non-blank line, so deeper
+    /// indentation stays part of the line.
+    fn consume_block_scalar(&mut self, tokens: &mut Vec<Token<'g>>, node_column: usize) {
+        let explicit_indent = self
+            .block_scalar_indent
+            .take()
+            .map(|indicator| node_column.saturating_sub(1) + indicator);
+        let min_indent = explicit_indent.unwrap_or(node_column.max(1));
+        let mut content_indent = explicit_indent;
+
+        while self.current < self.source.len() {
+            let rest = &self.source[self.current..];
+            let line_len = rest.find('\n').unwrap_or(rest.len());
+            let line = &rest[..line_len];
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            let blank = line.trim().is_empty();
+
+            if !blank {
+                if spaces < min_indent {
+                    break;
+                }
+                let indent = *content_indent.get_or_insert(spaces);
+                let indent = indent.min(spaces);
+                let start = self.current + indent;
+                tokens.push(Token::new(
+                    TokenKind::Identifier,
+                    &self.source[start..self.current + line_len],
+                    self.line,
+                    indent + 1,
+                ));
+            } else if let Some(indent) = content_indent.filter(|&indent| spaces > indent) {
+                // Spaces past the content indentation are part of the line
+                tokens.push(Token::new(
+                    TokenKind::Identifier,
+                    &self.source[self.current + indent..self.current + spaces],
+                    self.line,
+                    indent + 1,
+                ));
+            }
+
+            let mut end = self.current + line_len;
+            if end < self.source.len() {
+                tokens.push(Token::new(
+                    TokenKind::NewLine,
+                    "\n",
+                    self.line,
+                    line_len + 1,
+                ));
+                end += 1;
+                self.line += 1;
+            } else {
+                self.column = line_len + 1;
+            }
+            while matches!(self.chars.peek(), Some(&(index, _)) if index < end) {
+                self.chars.next();
+            }
+            self.current = end;
+        }
+    }
+
     fn peek_char(&mut self) -> Option<char> {
         self.chars.peek().map(|(_, c)| *c)
     }
@@ -315,39 +492,114 @@ impl<'g> Lexer<'g> {
                 break;
             }
             self.chars.next();
-            end = index + 1;
+            end = index + c.len_utf8();
         }
         end
     }
 
+    /// Update the line and column past a token that may span lines
+    fn move_past(&mut self, text: &str) {
+        match text.rfind('\n') {
+            Some(last_break) => {
+                self.line += text.matches('\n').count();
+                self.column = text[last_break + 1..].chars().count() + 1;
+            }
+            None => self.column += text.chars().count(),
+        }
+    }
+
+    /// Read a quoted string, which may span lines, up to its closing quote.
+    /// Double-quoted strings escape with `\`, single-quoted ones with `''`.
     fn consume_quoted_string(&mut self, start: usize, quote: char) -> usize {
         let mut end = start + 1;
         let mut escaped = false;
 
-        for (index, c) in self.chars.by_ref() {
-            end = index + 1;
+        while let Some((index, c)) = self.chars.next() {
+            end = index + c.len_utf8();
             if escaped {
                 escaped = false;
                 continue;
             }
-            if c == '\\' {
+            if c == '\\' && quote == '"' {
                 escaped = true;
             } else if c == quote {
+                if quote == '\'' && self.chars.next_if(|&(_, next)| next == '\'').is_some() {
+                    end += 1;
+                    continue;
+                }
                 break;
             }
         }
         end
     }
 
-    fn consume_simple_value(&mut self, start: usize) -> usize {
+    fn consume_name(&mut self, start: usize) -> usize {
         let mut end = start + 1;
+        while let Some(&(index, c)) = self.chars.peek() {
+            if !anchors::is_name_char(c) {
+                break;
+            }
+            self.chars.next();
+            end = index + c.len_utf8();
+        }
+        end
+    }
+
+    fn consume_tag(&mut self, start: usize) -> usize {
+        let end = start + tag_len(&self.source[start..]);
+        while self.chars.next_if(|&(index, _)| index < end).is_some() {}
+        end
+    }
+
+    /// Read a flow collection up to its matching closing bracket, across
+    /// lines, skipping brackets in quoted strings and comments. Without one,
+    /// it runs to the end of the input.
+    fn consume_flow(&mut self, start: usize) -> usize {
+        let mut depth = 1;
+        let mut end = start + 1;
+        let mut previous = ' ';
+        while let Some((index, c)) = self.chars.next() {
+            end = index + c.len_utf8();
+            match c {
+                '[' | '{' => depth += 1,
+                ']' | '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                '"' | '\'' if previous.is_whitespace() || "[{,:".contains(previous) => {
+                    end = self.consume_quoted_string(index, c)
+                }
+                '#' if previous.is_whitespace() => end = self.consume_comment(index),
+                _ => {}
+            }
+            previous = c;
+        }
+        end
+    }
+
+    fn consume_simple_value(&mut self, start: usize) -> usize {
+        let first_len = self.source[start..]
+            .chars()
+            .next()
+            .map_or(1, char::len_utf8);
+        let mut end = start + first_len;
 
         // Consume any characters that could be part of an unquoted value
         while let Some(&(index, c)) = self.chars.peek() {
-            // Stop at YAML structural characters
-            if matches!(c, ':' | '#' | '\n' | '\r') {
+            // Stop at YAML structural characters. A colon only ends the value
+            // when followed by whitespace, so `https://host` and `12:30` stay whole
+            if matches!(c, '#' | '\n' | '\r') {
                 break;
             }
+            if c == ':' {
+                let mut temp = self.chars.clone();
+                temp.next();
+                if matches!(temp.peek(), None | Some(&(_, ' ' | '\t' | '\n' | '\r'))) {
+                    break;
+                }
+            }
 
             // Handle whitespace - stop if followed by structural chars
             if matches!(c, ' ' | '\t') {
@@ -364,7 +616,7 @@ impl<'g> Lexer<'g> {
 
             // Consume the character
             self.chars.next();
-            end = index + 1;
+            end = index + c.len_utf8();
         }
 
         // Trim trailing whitespace
@@ -374,6 +626,59 @@ impl<'g> Lexer<'g> {
     }
 }
 
+/// Turn `\r\n` and lone `\r` line breaks into `\n`, the only break the lexer
+/// knows. Line and column numbers stay the same.
+pub(crate) fn normalize_line_breaks(source: &str) -> Cow<'_, str> {
+    if source.contains('\r') {
+        Cow::Owned(source.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(source)
+    }
+}
+
+/// Remove the indentation shared by all non-blank lines.
+pub(crate) fn dedent(source: &str) -> Cow<'_, str> {
+    let indent_of = |line: &str| line.len() - line.trim_start_matches(' ').len();
+    let common = source
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(indent_of)
+        .min()
+        .unwrap_or(0);
+    if common == 0 {
+        return Cow::Borrowed(source);
+    }
+    let lines: Vec<&str> = source
+        .split('\n')
+        .map(|line| &line[indent_of(line).min(common)..])
+        .collect();
+    Cow::Owned(lines.join("\n"))
+}
+
+/// The indentation indicator in a block scalar header such as `|2-`
+fn indentation_indicator(header: &str) -> Option<usize> {
+    header
+        .chars()
+        .find_map(|c| c.to_digit(10))
+        .map(|digit| digit as usize)
+}
+
+/// Length of the tag at the start of `text`: `!<...>` in verbatim form,
+/// otherwise everything up to whitespace or a flow indicator
+pub(crate) fn tag_len(text: &str) -> usize {
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    if text.starts_with("!<") {
+        return text[..end].find('>').map_or(end, |index| index + 1);
+    }
+    text[..end].find(|c| ",[]{}".contains(c)).unwrap_or(end)
+}
+
+/// Characters with a structural meaning in YAML that cannot start a plain
+/// value. `&` and `*` without a name after them are read as text.
+fn is_indicator(c: char) -> bool {
+    "-?:,[]{}#!|>'\"%@`".contains(c)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,6 +763,87 @@ mod tests {
         assert_eq!(number_tokens.len(), 4);
     }
 
+    #[test]
+    fn test_flow_collection() {
+        let source = "k: [a, \"b]\",\n  c] # done\nnext: {x: y}";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+
+        let flows: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Flow)
+            .collect();
+        assert_eq!(flows[0].text, "[a, \"b]\",\n  c]");
+        assert_eq!(flows[1].text, "{x: y}");
+
+        // Positions after a multi-line collection stay right
+        let comment = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::Comment)
+            .unwrap();
+        assert_eq!((comment.line, comment.column), (2, 6));
+        assert_eq!((flows[1].line, flows[1].column), (3, 7));
+    }
+
+    #[test]
+    fn test_tags() {
+        let source = "a: !Ref x\nb: !<tag:yaml.org,2002:str> y\nc: !!str\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+
+        let tags: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Tag)
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(tags, vec!["!Ref", "!<tag:yaml.org,2002:str>", "!!str"]);
+        assert_eq!(tag_len("!GetAtt [a, b]"), 7);
+        assert_eq!(tag_len("!t,b]"), 2);
+    }
+
+    #[test]
+    fn test_plain_values() {
+        let source = "cert: /etc/app/cert.pem\nurl: https://example.com:8443\nat: 12:30\nname: \u{e9}t\u{e9}\nkey:value";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+
+        // A colon only ends a plain value when whitespace or the line end follows
+        let values: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Identifier)
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                "cert",
+                "/etc/app/cert.pem",
+                "url",
+                "https://example.com:8443",
+                "at",
+                "12:30",
+                "name",
+                "\u{e9}t\u{e9}",
+                "key:value",
+            ]
+        );
+        assert!(is_indicator('|') && is_indicator('#') && !is_indicator('/'));
+    }
+
+    #[test]
+    fn test_block_header_keeps_chomping_indicator() {
+        let source = "a: |+\n  kept\n\nb: >-\n  stripped";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+
+        let headers: Vec<_> = tokens
+            .iter()
+            .filter(|t| matches!(t.kind, TokenKind::Pipe | TokenKind::GreaterThan))
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(headers, vec!["|+", ">-"]);
+    }
+
     #[test]
     fn test_strings() {
         let source = r#"single: 'hello world'
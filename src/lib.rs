@@ -6,9 +6,27 @@
 //! ## Features
 //!
 //! - All scalar values are strings (no implicit type conversion)
+//! - Keeps blank values (`key:`) apart from `""`, `[]` and `{}`
 //! - Supports basic YAML structures (objects, arrays, scalars)
-//! - Preserves comments during parsing
-//! - Supports multiline strings (literal `|` and folded `>`)
+//! - Preserves comments during parsing, with a path-based API for editing them
+//! - Reads `@name: value` annotations from comments as metadata
+//! - Generates reference docs from commented templates (see [`docs`])
+//! - Supports multiline strings (literal `|` and folded `>`) with indentation indicators
+//! - Folds plain and quoted scalars that continue over several lines
+//! - Parses flow collections (`[a, b]` and `{k: v}`)
+//! - Expands `&anchor` and `*alias` with limits, or keeps them, when enabled in [`ParseOptions`]
+//! - Reads `\n` and `\r\n` line breaks, and can write back the one a file uses
+//! - Reads and writes UTF-8, UTF-16 and UTF-32 with [`parse_bytes`] and [`emit_bytes`],
+//!   or [`parse_documents_bytes`] and [`emit_documents_bytes`] for streams
+//! - Configurable output style via [`emit_with`] and [`EmitOptions`]
+//! - Indented snippets with [`emit_fragment`] and [`parse_fragment`]
+//! - Round-trip checked output with [`emit_checked`]
+//! - Multi-document streams with [`parse_documents`] and [`emit_documents`]
+//! - Keeps `%YAML` and `%TAG` directives on each [`Document`]
+//! - Opt-in `<<` merge keys with [`resolve_merge_keys`]
+//! - Keeps tags such as `!Ref` and `!!str` as written, without interpreting them
+//! - Pluggable quoting policies ([`QuotingPolicy`]) and per-path scalar styles
+//! - Streams output into any `io::Write` or `fmt::Write`; `YamlNode` implements `Display`
 //! - Zero dependencies
 //! - Predictable, secure behavior
 //!
@@ -36,15 +54,40 @@
 //! ```
 
 #![deny(clippy::all)]
+mod anchors;
+mod annotations;
+mod commented;
+mod comments;
+pub mod docs;
+mod document;
 mod emitter;
+mod encoding;
+mod flow;
 mod lexer;
+mod merge;
+mod options;
 mod parser;
+mod path;
+mod quoting;
+mod roundtrip;
 mod types;
 
-pub use types::{YamlNode, YamlObject, YamlValue};
+pub use commented::CommentedEntry;
+pub use comments::{CommentEntry, CommentSlot};
+pub use document::{Directive, Document};
+pub use encoding::Encoding;
+pub use options::{
+    AliasMode, EmitOptions, FlowStyle, LineEnding, ParseOptions, QuoteStyle, ScalarStyle,
+    SequenceIndent,
+};
+pub use quoting::{AlwaysQuote, Conservative, Minimal, QuotingPolicy, Standard};
+pub use roundtrip::RoundTripError;
+pub use types::{CollectionStyle, YamlNode, YamlObject, YamlValue};
 
 use emitter::Emitter;
 use parser::Parser;
+use std::fmt;
+use std::io::{self, Write};
 
 /// Parse a YAML string into a `YamlNode`.
 ///
@@ -59,8 +102,202 @@ use parser::Parser;
 /// let parsed = parse(yaml).expect("Failed to parse");
 /// ```
 pub fn parse(yaml: &str) -> Result<YamlNode, String> {
-    let mut parser = Parser::new(yaml);
-    parser.parse()
+    parse_with(yaml, &ParseOptions::default())
+}
+
+/// Parse a YAML string with the settings in `options`, such as expanding
+/// aliases.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{parse, parse_with, AliasMode, ParseOptions};
+///
+/// let yaml = "defaults: &defaults\n  retries: \"3\"\njob:\n  settings: *defaults";
+/// assert!(parse(yaml).is_err());
+///
+/// let options = ParseOptions::new().with_aliases(AliasMode::Expand { max_expansion: 100 });
+/// let parsed = parse_with(yaml, &options).expect("Failed to parse");
+/// assert_eq!(parsed.get_path("job.settings.retries").and_then(|n| n.as_str()), Some("3"));
+/// ```
+pub fn parse_with(yaml: &str, options: &ParseOptions) -> Result<YamlNode, String> {
+    single_document(parse_documents_with(yaml, options)?).map(|document| document.root)
+}
+
+/// Parse an indented snippet, such as one copied out of a larger file.
+///
+/// The indentation shared by all non-blank lines is removed first.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::parse_fragment;
+///
+/// let snippet = "    image: nginx\n    ports:\n      - \"80\"\n";
+/// let parsed = parse_fragment(snippet).expect("Failed to parse");
+/// assert_eq!(parsed.get("image").and_then(|n| n.as_str()), Some("nginx"));
+/// ```
+pub fn parse_fragment(yaml: &str) -> Result<YamlNode, String> {
+    parse(&lexer::dedent(yaml))
+}
+
+/// Parse a YAML string into a [`Document`], recording the line ending it
+/// uses so [`emit_document`] can write it back the same way.
+///
+/// The document may be wrapped in `---` and `...` markers, but a stream of
+/// several documents is an error; use [`parse_documents`] for those.
+pub fn parse_document(yaml: &str) -> Result<Document, String> {
+    single_document(parse_documents(yaml)?)
+}
+
+fn single_document(mut documents: Vec<Document>) -> Result<Document, String> {
+    match documents.len() {
+        0 => Err("Unexpected end of input".to_string()),
+        1 => Ok(documents.remove(0)),
+        count => Err(format!(
+            "Expected a single document, found {}; use parse_documents for streams",
+            count
+        )),
+    }
+}
+
+/// Parse a stream of documents separated by `---` and ended by `...`
+/// markers, such as a bundle of Kubernetes manifests.
+///
+/// A document after a `---` marker with nothing in it has a blank root.
+/// Comments before a document's `---` marker are kept as its
+/// [`Document::header_comment`]. A stream with no markers and nothing but
+/// comments gives no documents.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_documents, parse_documents};
+///
+/// let stream = "kind: Service\n---\n# Backend\nkind: Deployment\n";
+/// let documents = parse_documents(stream).expect("Failed to parse");
+/// assert_eq!(documents.len(), 2);
+/// assert_eq!(documents[1].root.get("kind").and_then(|k| k.as_str()), Some("Deployment"));
+/// assert_eq!(
+///     emit_documents(&documents),
+///     "kind: Service\n---\n# Backend\nkind: Deployment"
+/// );
+/// ```
+pub fn parse_documents(yaml: &str) -> Result<Vec<Document>, String> {
+    parse_documents_with(yaml, &ParseOptions::default())
+}
+
+/// [`parse_documents`] with the settings in `options`. Each document has
+/// its own anchors.
+pub fn parse_documents_with(yaml: &str, options: &ParseOptions) -> Result<Vec<Document>, String> {
+    let line_ending = LineEnding::detect(yaml);
+    let bom = yaml.starts_with(encoding::BOM);
+    let yaml = yaml.strip_prefix(encoding::BOM).unwrap_or(yaml);
+    // `\r\n` line breaks are read as `\n`
+    let yaml = lexer::normalize_line_breaks(yaml);
+
+    let parts = document::split_documents(&yaml)?;
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let mut parser = Parser::new(&part.text).with_aliases(options.aliases);
+            let root = parser.parse().map_err(|error| {
+                if count > 1 {
+                    format!("Document {}: {}", index + 1, error)
+                } else {
+                    error
+                }
+            })?;
+            Ok(Document {
+                root,
+                line_ending,
+                encoding: Encoding::Utf8,
+                bom: bom && index == 0,
+                explicit_start: part.explicit_start,
+                explicit_end: part.explicit_end,
+                directives: part.directives,
+                header_comment: part.header_comment,
+            })
+        })
+        .collect()
+}
+
+/// Apply YAML 1.1 merge keys: replace each `<<` entry with the pairs of
+/// the mapping it refers to, or of each mapping in a sequence of them.
+///
+/// Keys already in the mapping win, then those of earlier mappings in the
+/// sequence. Merged pairs go where the `<<` entry was, keeping their
+/// comments. Aliases are resolved against the anchors before them, so this
+/// works on trees parsed with [`AliasMode::Preserve`] as well as expanded
+/// ones. Nothing is merged unless this is called.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit, parse_with, resolve_merge_keys, AliasMode, ParseOptions};
+///
+/// let yaml = "base: &base\n  image: nginx\n  replicas: \"1\"\nweb:\n  <<: *base\n  replicas: \"3\"";
+/// let options = ParseOptions::new().with_aliases(AliasMode::Preserve);
+/// let mut parsed = parse_with(yaml, &options).unwrap();
+/// resolve_merge_keys(&mut parsed).unwrap();
+/// assert!(emit(&parsed).ends_with("web:\n  image: nginx\n  replicas: \"3\""));
+/// ```
+pub fn resolve_merge_keys(node: &mut YamlNode) -> Result<(), String> {
+    merge::resolve(node)
+}
+
+/// Parse YAML from raw bytes in UTF-8, UTF-16 or UTF-32, with or without a
+/// byte order mark. The encoding is detected as the YAML spec describes and
+/// recorded in the [`Document`] so [`emit_bytes`] can write it back.
+///
+/// Like [`parse_document`], this reads a single document; use
+/// [`parse_documents_bytes`] for streams.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_bytes, parse_bytes, Encoding};
+///
+/// let bytes = b"\xFF\xFEn\x00:\x00 \x00x\x00";
+/// let document = parse_bytes(bytes).expect("Failed to parse");
+/// assert_eq!(document.encoding, Encoding::Utf16Le);
+/// assert!(document.bom);
+/// assert_eq!(document.root.get("n").and_then(|n| n.as_str()), Some("x"));
+/// assert_eq!(emit_bytes(&document), bytes);
+/// ```
+pub fn parse_bytes(bytes: &[u8]) -> Result<Document, String> {
+    let (encoding, _) = Encoding::detect(bytes);
+    let yaml = encoding.decode(bytes)?;
+    Ok(Document {
+        encoding,
+        ..parse_document(&yaml)?
+    })
+}
+
+/// [`parse_bytes`] for a stream of documents, each recording the encoding
+/// of the stream.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_documents_bytes, parse_documents_bytes, Encoding};
+///
+/// let bytes = b"\xEF\xBB\xBFa: x\n---\nb: y";
+/// let documents = parse_documents_bytes(bytes).expect("Failed to parse");
+/// assert_eq!(documents.len(), 2);
+/// assert!(documents.iter().all(|d| d.encoding == Encoding::Utf8));
+/// assert_eq!(emit_documents_bytes(&documents), bytes);
+/// ```
+pub fn parse_documents_bytes(bytes: &[u8]) -> Result<Vec<Document>, String> {
+    let (encoding, _) = Encoding::detect(bytes);
+    let yaml = encoding.decode(bytes)?;
+    let mut documents = parse_documents(&yaml)?;
+    for document in &mut documents {
+        document.encoding = encoding;
+    }
+    Ok(documents)
 }
 
 /// Emit a `YamlNode` back to a YAML string.
@@ -82,3 +319,250 @@ pub fn emit(node: &YamlNode) -> String {
     let mut emitter = Emitter::new();
     emitter.emit(node)
 }
+
+/// Emit a `YamlNode` using the formatting in `options`.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_with, parse, EmitOptions};
+///
+/// let parsed = parse("# Service\nname: api").expect("Failed to parse");
+/// let options = EmitOptions::new().with_comments(false);
+/// assert_eq!(emit_with(&parsed, &options), "name: api");
+/// ```
+pub fn emit_with(node: &YamlNode, options: &EmitOptions) -> String {
+    let mut emitter = Emitter::with_options(options.clone());
+    emitter.emit(node)
+}
+
+/// Emit a `YamlNode`, first checking that the output parses back to the
+/// same tree, comments included.
+///
+/// Use this before writing a file that must not change on its way through
+/// YAML: values the emitter can't represent faithfully are reported with
+/// the path of the first difference instead of being written.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_checked, parse, YamlNode, YamlValue};
+///
+/// let parsed = parse("name: api # Public").expect("Failed to parse");
+/// assert_eq!(emit_checked(&parsed).unwrap(), "name: api # Public");
+///
+/// let mut node = parsed.clone();
+/// *node.get_mut("name").unwrap() = YamlNode::from_value(YamlValue::String("say \"hi\"".into()));
+/// let error = emit_checked(&node).unwrap_err();
+/// assert_eq!(error.path, "name");
+/// ```
+pub fn emit_checked(node: &YamlNode) -> Result<String, RoundTripError> {
+    emit_checked_with(node, &EmitOptions::default())
+}
+
+/// [`emit_checked`] using the formatting in `options`.
+///
+/// Key order isn't compared in canonical mode, nor comments when
+/// [`EmitOptions::with_comments`] turns them off. Comments are compared
+/// word by word, so [`EmitOptions::with_comment_width`] may rewrap them.
+pub fn emit_checked_with(node: &YamlNode, options: &EmitOptions) -> Result<String, RoundTripError> {
+    roundtrip::check(node, emit_with(node, options), options)
+}
+
+/// Emit a `YamlNode` as a snippet to embed in a larger file, with every
+/// line indented by `base_indent` spaces.
+///
+/// A root scalar keeps its inline comment on its line, rather than the
+/// comment being written at the end as [`emit`] does for documents. A block
+/// collection has no line of its own, so its comment is written at the end.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_fragment, parse};
+///
+/// let parsed = parse("image: nginx\nports:\n  - \"80\"").expect("Failed to parse");
+/// assert_eq!(
+///     emit_fragment(&parsed, 4),
+///     "    image: nginx\n    ports:\n      - \"80\""
+/// );
+/// ```
+pub fn emit_fragment(node: &YamlNode, base_indent: usize) -> String {
+    emit_fragment_with(node, base_indent, &EmitOptions::default())
+}
+
+/// Emit a `YamlNode` as an indented snippet using the formatting in `options`.
+pub fn emit_fragment_with(node: &YamlNode, base_indent: usize, options: &EmitOptions) -> String {
+    let mut output = String::new();
+    // Writing to a String cannot fail
+    Emitter::with_output(&mut output, options.clone())
+        .write_fragment(node, base_indent)
+        .unwrap();
+    output
+}
+
+/// Emit a [`Document`] with the conventions recorded when it was parsed,
+/// including its `---` and `...` markers.
+///
+/// Use [`Document::emit_options`] with [`emit_with`] to change other settings
+/// while keeping those.
+pub fn emit_document(document: &Document) -> String {
+    emit_documents(std::slice::from_ref(document))
+}
+
+/// Emit a stream of documents, each after a `---` marker except for a
+/// first one that didn't have it and has no directives or header comment.
+pub fn emit_documents(documents: &[Document]) -> String {
+    let mut output = String::new();
+    let mut ended = true;
+    for (index, document) in documents.iter().enumerate() {
+        let line_ending = document.line_ending.as_str();
+        if index > 0 && !output.ends_with(line_ending) {
+            output.push_str(line_ending);
+        }
+        let has_header = document.header_comment.is_some() || !document.directives.is_empty();
+        // Header comments and directives can only follow a document that was
+        // ended with `...`
+        if has_header && !ended {
+            output.push_str("...");
+            output.push_str(line_ending);
+        }
+        let header = document.header_comment.as_deref().unwrap_or_default();
+        for line in header.lines() {
+            output.push('#');
+            if !line.is_empty() {
+                output.push(' ');
+                output.push_str(line);
+            }
+            output.push_str(line_ending);
+        }
+        for directive in &document.directives {
+            output.push_str(&directive.to_string());
+            output.push_str(line_ending);
+        }
+        let content = emit_with(&document.root, &document.emit_options());
+        if index > 0 || document.explicit_start || has_header {
+            output.push_str("---");
+            // An empty document is just its marker
+            if !content.is_empty() {
+                output.push_str(line_ending);
+            }
+        }
+        output.push_str(&content);
+        if document.explicit_end {
+            if !output.ends_with(line_ending) {
+                output.push_str(line_ending);
+            }
+            output.push_str("...");
+        }
+        ended = document.explicit_end;
+    }
+    output
+}
+
+/// Emit a [`Document`] as bytes in the encoding it was parsed from, with a
+/// byte order mark if it had one.
+pub fn emit_bytes(document: &Document) -> Vec<u8> {
+    document
+        .encoding
+        .encode(&emit_document(document), document.bom)
+}
+
+/// Emit a stream of documents as bytes in the encoding of the first one,
+/// with a byte order mark if it had one. An empty stream gives no bytes.
+pub fn emit_documents_bytes(documents: &[Document]) -> Vec<u8> {
+    match documents.first() {
+        Some(first) => first.encoding.encode(&emit_documents(documents), first.bom),
+        None => Vec::new(),
+    }
+}
+
+/// Stream a `YamlNode` into an `io::Write`, such as a file, without building
+/// the whole document in memory.
+///
+/// Output is buffered internally and flushed before returning, so the
+/// writer doesn't need its own buffering.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_to_writer, parse};
+///
+/// let parsed = parse("name: api").expect("Failed to parse");
+/// let mut file = Vec::new();
+/// emit_to_writer(&parsed, &mut file).expect("Failed to write");
+/// assert_eq!(file, b"name: api");
+/// ```
+pub fn emit_to_writer<W: io::Write>(node: &YamlNode, writer: W) -> io::Result<()> {
+    emit_to_writer_with(node, writer, &EmitOptions::default())
+}
+
+/// Stream a `YamlNode` into an `io::Write` using the formatting in `options`.
+pub fn emit_to_writer_with<W: io::Write>(
+    node: &YamlNode,
+    writer: W,
+    options: &EmitOptions,
+) -> io::Result<()> {
+    let mut output = IoWriter {
+        inner: io::BufWriter::new(writer),
+        error: None,
+    };
+    let result = Emitter::with_output(&mut output, options.clone()).write_document(node);
+    if let Some(error) = output.error {
+        return Err(error);
+    }
+    // Errors only come from the writer, and those were caught above
+    result.map_err(|_| io::Error::new(io::ErrorKind::Other, "formatter error"))?;
+    output.inner.flush()
+}
+
+/// Stream a `YamlNode` into a `fmt::Write`, such as a `Formatter`.
+///
+/// `YamlNode` implements `Display` with this, so `to_string()` and
+/// `format!` print YAML as well.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_to_fmt, parse};
+///
+/// let parsed = parse("name: api").expect("Failed to parse");
+/// let mut output = String::new();
+/// emit_to_fmt(&parsed, &mut output).expect("Failed to write");
+/// assert_eq!(output, "name: api");
+/// assert_eq!(parsed.to_string(), "name: api");
+/// ```
+pub fn emit_to_fmt<W: fmt::Write>(node: &YamlNode, writer: W) -> fmt::Result {
+    emit_to_fmt_with(node, writer, &EmitOptions::default())
+}
+
+/// Stream a `YamlNode` into a `fmt::Write` using the formatting in `options`.
+pub fn emit_to_fmt_with<W: fmt::Write>(
+    node: &YamlNode,
+    writer: W,
+    options: &EmitOptions,
+) -> fmt::Result {
+    Emitter::with_output(writer, options.clone()).write_document(node)
+}
+
+impl fmt::Display for YamlNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        emit_to_fmt(self, f)
+    }
+}
+
+/// Adapts an `io::Write` for the emitter, keeping the `io::Error` that
+/// `fmt::Write` can't carry
+struct IoWriter<W: io::Write> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
@@ -0,0 +1,117 @@
+//! Merge keys (`<<`), resolved on request after parsing.
+
+use crate::path;
+use crate::types::{YamlNode, YamlObject, YamlValue};
+use std::collections::HashMap;
+
+const MERGE_KEY: &str = "<<";
+
+/// Replace every `<<` entry in `node` with the pairs it merges in. The tree
+/// is left unchanged if any merge key can't be resolved.
+pub(crate) fn resolve(node: &mut YamlNode) -> Result<(), String> {
+    let mut merged = node.clone();
+    Merger::default().node(&mut merged, "")?;
+    *node = merged;
+    Ok(())
+}
+
+/// Anchored nodes seen so far, with their own merge keys already resolved,
+/// for aliases after them to refer to
+#[derive(Default)]
+struct Merger {
+    anchors: HashMap<String, YamlNode>,
+}
+
+impl Merger {
+    fn node(&mut self, node: &mut YamlNode, at: &str) -> Result<(), String> {
+        match &mut node.value {
+            YamlValue::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.node(item, &path::join(at, &index.to_string()))?;
+                }
+            }
+            YamlValue::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    self.node(value, &path::join(at, key))?;
+                }
+                self.merge(map, at)?;
+            }
+            YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+        }
+        if let Some(anchor) = &node.anchor {
+            self.anchors.insert(anchor.clone(), node.clone());
+        }
+        Ok(())
+    }
+
+    /// Insert the pairs merged in by the `<<` entry of `map` where the entry
+    /// was. Keys already in `map` win, then those of earlier mappings.
+    fn merge(&self, map: &mut YamlObject, at: &str) -> Result<(), String> {
+        let Some(start) = map.position(MERGE_KEY) else {
+            return Ok(());
+        };
+        let at = path::join(at, MERGE_KEY);
+        let Some(merge) = map.remove(MERGE_KEY) else {
+            return Ok(());
+        };
+        let sources = match &merge.value {
+            YamlValue::Array(items) => items
+                .iter()
+                .map(|item| self.mapping(item, &at))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => vec![self.mapping(&merge, &at)?],
+        };
+
+        let mut index = start;
+        for source in sources {
+            for (key, value) in source.iter() {
+                if map.contains_key(key) {
+                    continue;
+                }
+                let mut value = value.clone();
+                remove_anchors(&mut value);
+                map.insert_at(index, key.clone(), value);
+                index += 1;
+            }
+        }
+
+        // Comments on the `<<` entry go to the entry now in its place
+        let comments = [merge.leading_comment, merge.inline_comment];
+        let comments: Vec<String> = comments.into_iter().flatten().collect();
+        if !comments.is_empty() {
+            let last = map.len().saturating_sub(1);
+            if let Some((_, next)) = map.pair_at_mut(start.min(last)) {
+                let all = comments.into_iter().chain(next.leading_comment.take());
+                next.leading_comment = Some(all.collect::<Vec<_>>().join("\n"));
+            }
+        }
+        Ok(())
+    }
+
+    /// The mapping a merge key's value, or an item of it, stands for
+    fn mapping<'n>(&'n self, node: &'n YamlNode, at: &str) -> Result<&'n YamlObject, String> {
+        let target = match &node.value {
+            YamlValue::Alias(name) => self
+                .anchors
+                .get(name)
+                .ok_or_else(|| format!("Alias *{} has no anchor", name))?,
+            _ => node,
+        };
+        target.as_object().ok_or_else(|| {
+            format!(
+                "Merge key at {} must be a mapping, an alias to one, or a sequence of them",
+                at
+            )
+        })
+    }
+}
+
+/// Merged copies don't define the anchors of what they were copied from
+fn remove_anchors(node: &mut YamlNode) {
+    node.anchor = None;
+    match &mut node.value {
+        YamlValue::Array(items) => items.iter_mut().for_each(remove_anchors),
+        YamlValue::Object(map) => map.iter_mut().for_each(|(_, value)| remove_anchors(value)),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+    }
+}
@@ -0,0 +1,425 @@
+use crate::quoting::{Minimal, QuotingPolicy, Standard};
+use std::sync::Arc;
+
+/// How block sequences nested under a key are indented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SequenceIndent {
+    /// Dashes one indent level deeper than the key
+    ///
+    /// ```yaml
+    /// items:
+    ///   - a
+    /// ```
+    #[default]
+    Indented,
+    /// Dashes in the same column as the key
+    ///
+    /// ```yaml
+    /// items:
+    /// - a
+    /// ```
+    Flush,
+}
+
+/// Quote character used for values that need quoting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    #[default]
+    Double,
+    /// Single quotes, falling back to double quotes for values containing
+    /// `'`, `\` or control characters
+    Single,
+}
+
+/// Line break written between lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// The convention of the first line break in `text`, or `Lf` if there
+    /// is none.
+    ///
+    /// ```rust
+    /// use yamp::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::detect("a: 1\r\nb: 2\r\n"), LineEnding::CrLf);
+    /// assert_eq!(LineEnding::detect("a: 1"), LineEnding::Lf);
+    /// ```
+    pub fn detect(text: &str) -> Self {
+        match text.find('\n') {
+            Some(index) if text[..index].ends_with('\r') => LineEnding::CrLf,
+            _ => LineEnding::Lf,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// When collections without an explicit
+/// [`CollectionStyle`](crate::CollectionStyle) are written in flow style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlowStyle {
+    /// Always block style
+    #[default]
+    Never,
+    /// Flow style for non-empty arrays and objects of plain strings, with
+    /// no comments on their elements, whose line fits in `max_width` columns.
+    /// The root is always written in block style.
+    Short { max_width: usize },
+}
+
+/// Style forced on the strings at a path with
+/// [`EmitOptions::with_style_at`]
+///
+/// A style that can't represent a value, such as plain style for a string
+/// that has to be quoted, falls back to the usual choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarStyle {
+    /// Unquoted, regardless of the quoting policy
+    Plain,
+    /// Quoted with the configured [`QuoteStyle`]
+    Quoted,
+    /// A literal (`|`) block scalar
+    Literal,
+    /// A folded (`>`) block scalar, wrapped at the line width if one is set
+    Folded,
+}
+
+/// Settings for [`emit_with`](crate::emit_with).
+///
+/// The defaults produce the same output as [`emit`](crate::emit).
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_with, parse, EmitOptions, QuoteStyle, SequenceIndent};
+///
+/// let config = parse("# Hosts\nhosts:\n  - a\n  - \"\"").unwrap();
+/// let options = EmitOptions::new()
+///     .with_indent(4)
+///     .with_sequence_indent(SequenceIndent::Flush)
+///     .with_quote_style(QuoteStyle::Single)
+///     .with_comment_space(false);
+///
+/// assert_eq!(emit_with(&config, &options), "#Hosts\nhosts:\n- a\n- ''");
+/// ```
+#[derive(Debug, Clone)]
+pub struct EmitOptions {
+    pub(crate) indent: usize,
+    pub(crate) sequence_indent: SequenceIndent,
+    pub(crate) quote_style: QuoteStyle,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) comment_space: bool,
+    pub(crate) comments: bool,
+    pub(crate) align_comments: bool,
+    pub(crate) align_values: bool,
+    pub(crate) comment_width: Option<usize>,
+    pub(crate) line_width: Option<usize>,
+    pub(crate) flow_style: FlowStyle,
+    pub(crate) canonical: bool,
+    pub(crate) quoting: Arc<dyn QuotingPolicy>,
+    pub(crate) styles: Vec<(String, ScalarStyle)>,
+}
+
+impl EmitOptions {
+    pub fn new() -> Self {
+        EmitOptions {
+            indent: 2,
+            sequence_indent: SequenceIndent::default(),
+            quote_style: QuoteStyle::default(),
+            line_ending: LineEnding::default(),
+            comment_space: true,
+            comments: true,
+            align_comments: false,
+            align_values: false,
+            comment_width: None,
+            line_width: None,
+            flow_style: FlowStyle::default(),
+            canonical: false,
+            quoting: Arc::new(Standard),
+            styles: Vec::new(),
+        }
+    }
+
+    /// Options for canonical output: trees that differ only in key order or
+    /// in their [`CollectionStyle`](crate::CollectionStyle)s emit the same
+    /// bytes.
+    ///
+    /// Mapping keys are sorted, with their comments, except in a mapping
+    /// where that would change which anchor an alias refers to. The default
+    /// layout is used regardless of other settings, except
+    /// [`with_comments`](Self::with_comments) which can strip comments.
+    /// Strings are quoted with the [`Minimal`](crate::Minimal) policy, so
+    /// only where this crate would otherwise read them back differently.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::{emit_with, parse, EmitOptions};
+    ///
+    /// let a = parse("port: 80\n# Bind address\nhost: localhost").unwrap();
+    /// let b = parse("# Bind address\nhost: localhost\nport: \"80\"").unwrap();
+    ///
+    /// let options = EmitOptions::canonical();
+    /// assert_eq!(emit_with(&a, &options), emit_with(&b, &options));
+    /// assert_eq!(emit_with(&a, &options), "# Bind address\nhost: localhost\nport: 80");
+    /// ```
+    pub fn canonical() -> Self {
+        EmitOptions {
+            canonical: true,
+            quoting: Arc::new(Minimal),
+            ..Self::new()
+        }
+    }
+
+    /// Spaces per indentation level, at least 1. Defaults to 2.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent.max(1);
+        self
+    }
+
+    pub fn with_sequence_indent(mut self, sequence_indent: SequenceIndent) -> Self {
+        self.sequence_indent = sequence_indent;
+        self
+    }
+
+    pub fn with_quote_style(mut self, quote_style: QuoteStyle) -> Self {
+        self.quote_style = quote_style;
+        self
+    }
+
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Whether comments are written as `# text` (the default) or `#text`.
+    pub fn with_comment_space(mut self, comment_space: bool) -> Self {
+        self.comment_space = comment_space;
+        self
+    }
+
+    /// Whether comments are written at all. Defaults to `true`.
+    pub fn with_comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Line up the inline comments of a block's entries in one column, one
+    /// space after the longest commented line. Off by default.
+    ///
+    /// ```rust
+    /// use yamp::{emit_with, parse, EmitOptions};
+    ///
+    /// let parsed = parse("host: localhost # Bind address\nport: \"80\" # HTTP").unwrap();
+    /// let options = EmitOptions::new().with_align_comments(true);
+    /// assert_eq!(
+    ///     emit_with(&parsed, &options),
+    ///     "host: localhost # Bind address\nport: \"80\"      # HTTP"
+    /// );
+    /// ```
+    pub fn with_align_comments(mut self, align_comments: bool) -> Self {
+        self.align_comments = align_comments;
+        self
+    }
+
+    /// Line up the string values of a mapping in one column, one space
+    /// after the longest key. Off by default.
+    ///
+    /// ```rust
+    /// use yamp::{emit_with, parse, EmitOptions};
+    ///
+    /// let parsed = parse("name: api\nreplicas: \"2\"").unwrap();
+    /// let options = EmitOptions::new().with_align_values(true);
+    /// assert_eq!(emit_with(&parsed, &options), "name:     api\nreplicas: \"2\"");
+    /// ```
+    pub fn with_align_values(mut self, align_values: bool) -> Self {
+        self.align_values = align_values;
+        self
+    }
+
+    /// Wrap leading comment lines at spaces so they fit in `width` columns
+    /// where possible. Off by default.
+    ///
+    /// Lines starting with whitespace or an `@name:` annotation are kept as
+    /// they are.
+    pub fn with_comment_width(mut self, width: usize) -> Self {
+        self.comment_width = Some(width);
+        self
+    }
+
+    /// Wrap long single-line strings into folded (`>`) block scalars so
+    /// lines stay within `width` columns where possible. Off by default.
+    ///
+    /// Strings are only broken at single spaces before a letter or digit,
+    /// so they parse back unchanged. A value with an inline comment is
+    /// never folded.
+    pub fn with_line_width(mut self, width: usize) -> Self {
+        self.line_width = Some(width);
+        self
+    }
+
+    pub fn with_flow_style(mut self, flow_style: FlowStyle) -> Self {
+        self.flow_style = flow_style;
+        self
+    }
+
+    /// Policy for which strings are quoted. Defaults to
+    /// [`Standard`](crate::Standard).
+    pub fn with_quoting<P: QuotingPolicy + 'static>(mut self, policy: P) -> Self {
+        self.quoting = Arc::new(policy);
+        self
+    }
+
+    /// Write the strings at `path` in `style`. A `*` segment matches any
+    /// key or index, so `jobs.*.script` covers the `script` of every job.
+    /// When several paths match, the one added last wins.
+    ///
+    /// Collections in flow style ignore these, so a collection holding a
+    /// matching string is written in block style unless its
+    /// [`CollectionStyle`](crate::CollectionStyle) is `Flow`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use yamp::{emit_with, parse, EmitOptions, ScalarStyle};
+    ///
+    /// let parsed = parse("version: v2\nscript: make").unwrap();
+    /// let options = EmitOptions::new()
+    ///     .with_style_at("version", ScalarStyle::Quoted)
+    ///     .with_style_at("script", ScalarStyle::Literal);
+    /// assert_eq!(
+    ///     emit_with(&parsed, &options),
+    ///     "version: \"v2\"\nscript: |-\n  make"
+    /// );
+    /// ```
+    pub fn with_style_at(mut self, path: &str, style: ScalarStyle) -> Self {
+        self.styles.push((path.to_string(), style));
+        self
+    }
+
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    pub fn sequence_indent(&self) -> SequenceIndent {
+        self.sequence_indent
+    }
+
+    pub fn quote_style(&self) -> QuoteStyle {
+        self.quote_style
+    }
+
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    pub fn comment_space(&self) -> bool {
+        self.comment_space
+    }
+
+    pub fn comments(&self) -> bool {
+        self.comments
+    }
+
+    pub fn align_comments(&self) -> bool {
+        self.align_comments
+    }
+
+    pub fn align_values(&self) -> bool {
+        self.align_values
+    }
+
+    pub fn comment_width(&self) -> Option<usize> {
+        self.comment_width
+    }
+
+    pub fn line_width(&self) -> Option<usize> {
+        self.line_width
+    }
+
+    pub fn flow_style(&self) -> FlowStyle {
+        self.flow_style
+    }
+
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    pub fn quoting(&self) -> &dyn QuotingPolicy {
+        self.quoting.as_ref()
+    }
+}
+
+impl Default for EmitOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How `&anchor` and `*alias` are read by [`parse_with`](crate::parse_with)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AliasMode {
+    /// Anchors and aliases are an error
+    #[default]
+    Reject,
+    /// Each alias is replaced by a copy of the node its anchor is on.
+    ///
+    /// Parsing fails when the copies add up to more than `max_expansion`
+    /// nodes, so a small file can't expand into an enormous tree.
+    ///
+    /// Aliases also can't be nested more than 64 anchors deep. That limit
+    /// is fixed rather than configurable: it bounds how deeply the expanded
+    /// tree nests, which everything that walks the tree recursively relies
+    /// on, while `max_expansion` already bounds its size.
+    Expand { max_expansion: usize },
+    /// Anchors are kept on the nodes they're on, as
+    /// [`YamlNode::anchor`](crate::YamlNode::anchor), and each alias becomes
+    /// a [`YamlValue::Alias`](crate::YamlValue::Alias) node, so both are
+    /// written back unchanged. Aliases must still refer to an anchor before
+    /// them and outside of them.
+    Preserve,
+}
+
+/// Options for reading YAML with [`parse_with`](crate::parse_with).
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{parse_with, AliasMode, ParseOptions};
+///
+/// let yaml = "base: &base\n  image: nginx\nweb: *base";
+/// let options = ParseOptions::new().with_aliases(AliasMode::Expand { max_expansion: 1000 });
+/// let parsed = parse_with(yaml, &options).unwrap();
+/// assert_eq!(parsed.get_path("web.image").and_then(|n| n.as_str()), Some("nginx"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub(crate) aliases: AliasMode,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        ParseOptions::default()
+    }
+
+    /// How anchors and aliases are handled. Defaults to
+    /// [`AliasMode::Reject`].
+    pub fn with_aliases(mut self, aliases: AliasMode) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    pub fn aliases(&self) -> AliasMode {
+        self.aliases
+    }
+}
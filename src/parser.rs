@@ -1,4 +1,7 @@
+use crate::anchors::Anchors;
+use crate::flow;
 use crate::lexer::{Lexer, Token, TokenKind};
+use crate::options::AliasMode;
 use crate::types::{YamlNode, YamlObject, YamlValue};
 
 #[derive(Debug, Clone, Copy)]
@@ -8,28 +11,69 @@ enum ChompMode {
     Keep,  // + keep all trailing newlines
 }
 
+/// The anchor and tag written before a node
+#[derive(Default)]
+struct Properties {
+    anchored: bool,
+    tag: Option<String>,
+}
+
+impl Properties {
+    fn is_empty(&self) -> bool {
+        !self.anchored && self.tag.is_none()
+    }
+}
+
+/// Comment lines collected above a node, with the column of each `#`
+struct Comment {
+    lines: Vec<(String, usize)>,
+}
+
+impl Comment {
+    fn text(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(|(line, _)| line.as_str()).collect();
+        lines.join("\n")
+    }
+}
+
 pub(crate) struct Parser<'g> {
     tokens: Vec<Token<'g>>,
     current: usize,
+    anchors: Anchors,
+    /// Entry columns of the block collections being parsed, innermost last
+    blocks: Vec<usize>,
 }
 
 impl<'g> Parser<'g> {
     pub(crate) fn new(source: &'g str) -> Self {
         let mut lexer = Lexer::new(source);
         let tokens = lexer.tokenize();
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            anchors: Anchors::new(AliasMode::default()),
+            blocks: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_aliases(mut self, mode: AliasMode) -> Self {
+        self.anchors = Anchors::new(mode);
+        self
     }
 
-    fn collect_consecutive_comments(&mut self) -> Option<String> {
+    fn collect_consecutive_comments(&mut self) -> Option<Comment> {
         self.collect_consecutive_comments_internal(false)
     }
 
-    fn collect_consecutive_comments_check_identifier(&mut self) -> Option<String> {
+    fn collect_consecutive_comments_check_identifier(&mut self) -> Option<Comment> {
         self.collect_consecutive_comments_internal(true)
     }
 
-    fn collect_consecutive_comments_internal(&mut self, check_for_identifier: bool) -> Option<String> {
-        let mut leading_comments: Vec<String> = Vec::new();
+    fn collect_consecutive_comments_internal(
+        &mut self,
+        check_for_identifier: bool,
+    ) -> Option<Comment> {
+        let mut leading_comments: Vec<(String, usize)> = Vec::new();
 
         // First, look backward to find any comments that should be associated with this position
         // This handles cases where comments were already passed during array/object parsing
@@ -60,8 +104,8 @@ impl<'g> Parser<'g> {
 
                     // If we haven't found any significant non-comment content yet, this comment belongs to current position
                     if !found_non_comment_content {
-                        leading_comments
-                            .insert(0, token.text.trim_start_matches('#').trim().to_string());
+                        let text = token.text.trim_start_matches('#').trim().to_string();
+                        leading_comments.insert(0, (text, token.column));
                     } else {
                         // We found a comment but there's content between it and current position
                         // Check if there are only whitespace/newlines between this comment and current position
@@ -80,7 +124,11 @@ impl<'g> Parser<'g> {
                                     | TokenKind::Hyphen
                                     | TokenKind::Comment
                                     | TokenKind::Pipe
-                                    | TokenKind::GreaterThan => {
+                                    | TokenKind::GreaterThan
+                                    | TokenKind::Flow
+                                    | TokenKind::Anchor
+                                    | TokenKind::Tag
+                                    | TokenKind::Alias => {
                                         valid_comment = false; // Non-whitespace content between comment and current position
                                         break;
                                     }
@@ -89,8 +137,8 @@ impl<'g> Parser<'g> {
                         }
 
                         if valid_comment {
-                            leading_comments
-                                .insert(0, token.text.trim_start_matches('#').trim().to_string());
+                            let text = token.text.trim_start_matches('#').trim().to_string();
+                            leading_comments.insert(0, (text, token.column));
                         } else {
                             break; // Stop looking backward if we hit a non-associable comment
                         }
@@ -107,7 +155,11 @@ impl<'g> Parser<'g> {
                 | TokenKind::String
                 | TokenKind::Hyphen
                 | TokenKind::Pipe
-                | TokenKind::GreaterThan => {
+                | TokenKind::GreaterThan
+                | TokenKind::Flow
+                | TokenKind::Anchor
+                | TokenKind::Tag
+                | TokenKind::Alias => {
                     found_non_comment_content = true;
                     // Don't break yet - continue looking for more comments
                 }
@@ -127,6 +179,7 @@ impl<'g> Parser<'g> {
                 TokenKind::Comment => {
                     // Store comment text before any position changes
                     let comment_text = token.text.trim_start_matches('#').trim().to_string();
+                    let comment_column = token.column;
 
                     // If we should check for identifier, look ahead before consuming the comment
                     if check_for_identifier {
@@ -165,7 +218,7 @@ impl<'g> Parser<'g> {
                         }
                     }
 
-                    leading_comments.push(comment_text);
+                    leading_comments.push((comment_text, comment_column));
                     self.advance();
 
                     // Skip whitespace after comment
@@ -185,7 +238,11 @@ impl<'g> Parser<'g> {
                             | TokenKind::Hyphen
                             | TokenKind::Comment
                             | TokenKind::Pipe
-                            | TokenKind::GreaterThan => break,
+                            | TokenKind::GreaterThan
+                            | TokenKind::Flow
+                            | TokenKind::Anchor
+                            | TokenKind::Tag
+                            | TokenKind::Alias => break,
                         }
                     }
                 }
@@ -194,30 +251,70 @@ impl<'g> Parser<'g> {
                 | TokenKind::String
                 | TokenKind::Hyphen
                 | TokenKind::Pipe
-                | TokenKind::GreaterThan => break,
+                | TokenKind::GreaterThan
+                | TokenKind::Flow
+                | TokenKind::Anchor
+                | TokenKind::Tag
+                | TokenKind::Alias => break,
             }
         }
 
         if leading_comments.is_empty() {
             None
         } else {
-            Some(leading_comments.join("\n"))
+            Some(Comment {
+                lines: leading_comments,
+            })
         }
     }
 
+    /// Set a node's leading comment, working out from the column of each
+    /// line which block it belongs to. `previous` is the entry before the
+    /// node in the innermost open block.
+    fn attach_comment(&self, node: &mut YamlNode, comment: Comment, previous: Option<&YamlNode>) {
+        node.layout.comment_levels = comment
+            .lines
+            .iter()
+            .map(|&(_, column)| comment_level(column, &self.blocks, previous))
+            .collect();
+        node.leading_comment = Some(comment.text());
+    }
+
     pub(crate) fn parse(&mut self) -> Result<YamlNode, String> {
-        // Don't skip comments at the root level - parse_value will handle them
-        let mut result = self.parse_value(0)?;
+        // A document with nothing but comments, such as one between two `---`
+        // markers, is blank. Otherwise don't skip comments at the root
+        // level - parse_value will handle them
+        let only_comments = self.tokens.iter().all(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Comment
+                    | TokenKind::Whitespace
+                    | TokenKind::NewLine
+                    | TokenKind::Indent
+                    | TokenKind::Dedent
+            )
+        });
+        let mut result = if only_comments {
+            YamlNode::blank()
+        } else {
+            self.parse_value(0)?
+        };
 
         // Collect any trailing comments at the end of the document
         self.skip_whitespace_and_newlines();
         let mut trailing_comments = Vec::new();
+        let mut trailing_levels = Vec::new();
+        // Lines indented into the root's last entry belong to the blocks
+        // ending there
+        let root_block = last_entry(&result).and_then(|last| last.layout.column);
+        let blocks: Vec<usize> = root_block.into_iter().collect();
 
         while let Some(token) = self.current_token() {
             match token.kind {
                 TokenKind::Comment => {
                     let comment_text = token.text.trim_start_matches('#').trim();
                     trailing_comments.push(comment_text.to_string());
+                    trailing_levels.push(comment_level(token.column, &blocks, last_entry(&result)));
                     self.advance();
                     self.skip_whitespace_and_newlines();
                 }
@@ -229,10 +326,13 @@ impl<'g> Parser<'g> {
         if !trailing_comments.is_empty() {
             // If there's already an inline comment, append the trailing comments
             if let Some(existing) = result.inline_comment {
+                let existing_levels = vec![0; existing.lines().count()];
+                trailing_levels.splice(0..0, existing_levels);
                 result.inline_comment = Some(format!("{}\n{}", existing, trailing_comments.join("\n")));
             } else {
                 result.inline_comment = Some(trailing_comments.join("\n"));
             }
+            result.layout.trailing_levels = trailing_levels;
         }
 
         Ok(result)
@@ -277,7 +377,11 @@ impl<'g> Parser<'g> {
                 | TokenKind::Hyphen
                 | TokenKind::Comment
                 | TokenKind::Pipe
-                | TokenKind::GreaterThan => break,
+                | TokenKind::GreaterThan
+                | TokenKind::Flow
+                | TokenKind::Anchor
+                | TokenKind::Tag
+                | TokenKind::Alias => break,
             }
         }
     }
@@ -293,6 +397,19 @@ impl<'g> Parser<'g> {
         Some(comment.to_string())
     }
 
+    /// Whether the current token is a comment ending a line, with a block
+    /// collection starting on the next one
+    fn at_comment_before_block(&self) -> bool {
+        let kind = |offset: usize| {
+            self.tokens
+                .get(self.current + offset)
+                .map(|token| &token.kind)
+        };
+        kind(0) == Some(&TokenKind::Comment)
+            && kind(1) == Some(&TokenKind::NewLine)
+            && matches!(kind(2), Some(TokenKind::Indent | TokenKind::Hyphen))
+    }
+
     fn parse_value(&mut self, min_indent: usize) -> Result<YamlNode, String> {
         // Skip only whitespace initially, not comments
         self.skip_whitespace();
@@ -311,6 +428,16 @@ impl<'g> Parser<'g> {
         // Collect leading comment(s) - preserve only consecutive comments (no blank lines)
         let mut leading_comment = self.collect_consecutive_comments();
 
+        let properties = self.take_properties()?;
+        if !properties.is_empty() {
+            let mut node = self.parse_value(min_indent)?;
+            self.apply(properties, &mut node);
+            if let (None, Some(comment)) = (&node.leading_comment, leading_comment) {
+                self.attach_comment(&mut node, comment, None);
+            }
+            return Ok(node);
+        }
+
         let token = self
             .current_token()
             .ok_or_else(|| "Unexpected end of input".to_string())?;
@@ -320,7 +447,9 @@ impl<'g> Parser<'g> {
                 // Pass the leading comment to parse_array for the first item
                 // Take ownership of the comment to avoid cloning
                 let value = self.parse_array(min_indent, leading_comment.take())?;
-                YamlNode::from_value(value)
+                // A block sequence has no inline comment of its own; anything
+                // after the last item belongs to what follows
+                return Ok(YamlNode::from_value(value));
             }
             TokenKind::Identifier => {
                 let text = token.text;
@@ -337,25 +466,37 @@ impl<'g> Parser<'g> {
                 }
 
                 // It's a scalar value - always treat as string
-                YamlNode::from_value(YamlValue::String(text.to_string()))
+                let text = self.continue_plain(text.to_string(), min_indent);
+                YamlNode::from_value(YamlValue::String(text))
             }
             TokenKind::String => {
-                let text = token.text;
-                let content = if text.starts_with('"') || text.starts_with('\'') {
-                    &text[1..text.len() - 1]
-                } else {
-                    text
-                };
+                let content = unquote(token.text)?;
                 self.advance();
-                YamlNode::from_value(YamlValue::String(content.to_string()))
+                YamlNode::from_value(YamlValue::String(content))
+            }
+            TokenKind::Flow => {
+                let mut node = self.parse_flow()?;
+                if let Some(comment) = leading_comment {
+                    self.attach_comment(&mut node, comment, None);
+                }
+                return Ok(node);
+            }
+            TokenKind::Alias => self.parse_alias()?,
+            // A block scalar as the whole document, as in `--- |`
+            TokenKind::Pipe | TokenKind::GreaterThan => {
+                let mut node = self.parse_multiline_string(min_indent)?;
+                if let Some(comment) = leading_comment {
+                    self.attach_comment(&mut node, comment, None);
+                }
+                return Ok(node);
             }
             TokenKind::Whitespace
             | TokenKind::NewLine
             | TokenKind::Colon
             | TokenKind::Indent
             | TokenKind::Dedent
-            | TokenKind::Pipe
-            | TokenKind::GreaterThan => {
+            | TokenKind::Anchor
+            | TokenKind::Tag => {
                 return Err(format!("Unexpected token: {:?}", token.kind));
             }
             TokenKind::Comment => {
@@ -366,14 +507,16 @@ impl<'g> Parser<'g> {
 
         let inline_comment = self.collect_comment();
 
-        Ok(YamlNode::with_comments(
-            node.value,
-            leading_comment,
-            inline_comment,
-        ))
+        // An alias keeps the style of the collection it copies
+        let mut node =
+            YamlNode::with_comments(node.value, None, inline_comment).with_style(node.style);
+        if let Some(comment) = leading_comment {
+            self.attach_comment(&mut node, comment, None);
+        }
+        Ok(node)
     }
 
-    fn parse_inline_value(&mut self) -> Result<YamlNode, String> {
+    fn parse_inline_value(&mut self, parent_column: usize) -> Result<YamlNode, String> {
         // Collect tokens until we hit a newline or comment
         let start_token = self
             .current_token()
@@ -382,20 +525,21 @@ impl<'g> Parser<'g> {
         // Check for special single-token values first
         match start_token.kind {
             TokenKind::String => {
-                let text = start_token.text;
-                let content = if text.starts_with('"') || text.starts_with('\'') {
-                    &text[1..text.len() - 1]
-                } else {
-                    text
-                };
+                let content = unquote(start_token.text)?;
                 self.advance();
                 let inline_comment = self.collect_comment();
                 return Ok(YamlNode::with_comments(
-                    YamlValue::String(content.to_string()),
+                    YamlValue::String(content),
                     None,
                     inline_comment,
                 ));
             }
+            TokenKind::Flow => return self.parse_flow(),
+            TokenKind::Alias => {
+                let mut node = self.parse_alias()?;
+                node.inline_comment = self.collect_comment();
+                return Ok(node);
+            }
             TokenKind::Identifier
             | TokenKind::Colon
             | TokenKind::Whitespace
@@ -405,7 +549,9 @@ impl<'g> Parser<'g> {
             | TokenKind::Indent
             | TokenKind::Dedent
             | TokenKind::Pipe
-            | TokenKind::GreaterThan => {}
+            | TokenKind::GreaterThan
+            | TokenKind::Anchor
+            | TokenKind::Tag => {}
         }
 
         // Otherwise collect all tokens until newline or comment
@@ -426,7 +572,11 @@ impl<'g> Parser<'g> {
                 | TokenKind::Indent
                 | TokenKind::Dedent
                 | TokenKind::Pipe
-                | TokenKind::GreaterThan => {
+                | TokenKind::GreaterThan
+                | TokenKind::Flow
+                | TokenKind::Anchor
+                | TokenKind::Tag
+                | TokenKind::Alias => {
                     if value_parts.is_empty() && single_token_text.is_none() {
                         single_token_text = Some(token.text);
                     }
@@ -442,30 +592,208 @@ impl<'g> Parser<'g> {
         }
 
         // Everything is a string now
-        let value = if let Some(text) = single_token_text.filter(|_| value_parts.len() == 1) {
-            YamlValue::String(text.to_string())
+        let value_str = if let Some(text) = single_token_text.filter(|_| value_parts.len() == 1) {
+            text.to_string()
         } else {
             // For multi-token values, join them
-            let value_str = value_parts.join("");
-            YamlValue::String(value_str)
+            value_parts.join("")
         };
+        let value = YamlValue::String(self.continue_plain(value_str, parent_column));
 
         let inline_comment = self.collect_comment();
 
         Ok(YamlNode::with_comments(value, None, inline_comment))
     }
 
+    /// Fold the lines continuing a plain scalar onto its first line: those
+    /// indented past `parent_column` that don't start an item or a pair. A
+    /// comment ends the scalar.
+    fn continue_plain(&mut self, first: String, parent_column: usize) -> String {
+        let mut text = first;
+        let mut position = self.current;
+        while let Some(line_start) = self.continuation_line(position, parent_column) {
+            let breaks = self.tokens[position..line_start]
+                .iter()
+                .filter(|token| token.kind == TokenKind::NewLine)
+                .count();
+            text.extend(std::iter::repeat('\n').take(breaks));
+            position = line_start;
+            while let Some(token) = self.tokens.get(position) {
+                if matches!(token.kind, TokenKind::NewLine | TokenKind::Comment) {
+                    break;
+                }
+                text.push_str(token.text);
+                position += 1;
+            }
+            self.current = position;
+            if self
+                .current_token()
+                .is_some_and(|token| token.kind == TokenKind::Comment)
+            {
+                break;
+            }
+        }
+        flow::fold_lines(&text)
+    }
+
+    /// Where the line after the one ending at `position` starts, if it
+    /// continues a plain scalar
+    fn continuation_line(&self, position: usize, parent_column: usize) -> Option<usize> {
+        if self.tokens.get(position)?.kind != TokenKind::NewLine {
+            return None;
+        }
+        let start = position
+            + self.tokens[position..].iter().position(|token| {
+                !matches!(
+                    token.kind,
+                    TokenKind::NewLine
+                        | TokenKind::Whitespace
+                        | TokenKind::Indent
+                        | TokenKind::Dedent
+                )
+            })?;
+        let first = &self.tokens[start];
+        if first.column <= parent_column
+            || matches!(first.kind, TokenKind::Hyphen | TokenKind::Comment)
+        {
+            return None;
+        }
+        // A `key:` on the line would be a pair, not more of the scalar
+        let pair = self.tokens[start..]
+            .iter()
+            .take_while(|token| !matches!(token.kind, TokenKind::NewLine | TokenKind::Comment))
+            .any(|token| token.kind == TokenKind::Colon);
+        (!pair).then_some(start)
+    }
+
+    /// Read the anchor and tag before the node about to be parsed, in
+    /// either order, opening the anchor. The caller applies them to the
+    /// finished node.
+    fn take_properties(&mut self) -> Result<Properties, String> {
+        let mut properties = Properties::default();
+        self.skip_whitespace();
+        while let Some(token) = self.current_token() {
+            match token.kind {
+                TokenKind::Anchor if !properties.anchored => {
+                    let text = token.text;
+                    self.anchors.open(&text[1..])?;
+                    properties.anchored = true;
+                }
+                TokenKind::Tag if properties.tag.is_none() => {
+                    properties.tag = Some(token.text.to_string());
+                }
+                _ => break,
+            }
+            self.advance();
+            self.skip_whitespace();
+        }
+        // On the line of an implicit key they would belong to the key, and
+        // keys are plain strings
+        if !properties.is_empty() && self.at_key() {
+            return Err("Tags and anchors on mapping keys are not supported".to_string());
+        }
+        Ok(properties)
+    }
+
+    /// Whether a `key:` starts at the current token
+    fn at_key(&self) -> bool {
+        let mut tokens = self.tokens[self.current..].iter();
+        tokens
+            .next()
+            .is_some_and(|token| matches!(token.kind, TokenKind::Identifier | TokenKind::String))
+            && tokens
+                .find(|token| token.kind != TokenKind::Whitespace)
+                .is_some_and(|token| token.kind == TokenKind::Colon)
+    }
+
+    /// Give a node the properties written before it, closing its anchor
+    fn apply(&mut self, properties: Properties, node: &mut YamlNode) {
+        if properties.tag.is_some() {
+            node.tag = properties.tag;
+        }
+        if properties.anchored {
+            self.anchors.close(node);
+        }
+    }
+
+    /// Whether the line ends right after a node's `-`, `key:` or
+    /// properties, with no nested block below, so the node is blank. A
+    /// nested block starts to the right of `parent_column`, where the dash
+    /// or key is; after a key, a sequence may also start in the key's column.
+    fn at_empty_node(&self, parent_column: usize, after_key: bool) -> bool {
+        let mut tokens = self.tokens[self.current..].iter();
+        let Some(first) = tokens.find(|token| token.kind != TokenKind::Comment) else {
+            return true;
+        };
+        if !matches!(first.kind, TokenKind::NewLine | TokenKind::Dedent) {
+            return false;
+        }
+        let next = tokens.find(|token| {
+            !matches!(
+                token.kind,
+                TokenKind::NewLine
+                    | TokenKind::Whitespace
+                    | TokenKind::Indent
+                    | TokenKind::Dedent
+                    | TokenKind::Comment
+            )
+        });
+        match next {
+            Some(token) if token.column > parent_column => false,
+            Some(token) => {
+                !(after_key && token.kind == TokenKind::Hyphen && token.column == parent_column)
+            }
+            None => true,
+        }
+    }
+
+    /// A blank node, with the comment after its indicator or properties
+    fn empty_node(&mut self) -> YamlNode {
+        let inline_comment = self.collect_comment();
+        YamlNode::with_comments(YamlValue::Blank, None, inline_comment)
+    }
+
+    fn parse_alias(&mut self) -> Result<YamlNode, String> {
+        let text = self.current_token().map_or("*", |token| token.text);
+        let node = self.anchors.resolve(&text[1..])?;
+        self.advance();
+        Ok(node)
+    }
+
+    /// A flow collection and its inline comment, which must end the line
+    fn parse_flow(&mut self) -> Result<YamlNode, String> {
+        let text = self.current_token().map_or("", |token| token.text);
+        let mut node = flow::parse_flow(text, &mut self.anchors)?;
+        self.advance();
+        self.skip_whitespace();
+        if self
+            .current_token()
+            .is_some_and(|token| token.kind == TokenKind::Colon)
+        {
+            return Err("Collections as mapping keys are not supported".to_string());
+        }
+        node.inline_comment = self.collect_comment();
+        match self.current_token() {
+            Some(token) if !matches!(token.kind, TokenKind::NewLine | TokenKind::Dedent) => {
+                Err(format!("Unexpected {:?} after flow collection", token.kind))
+            }
+            _ => Ok(node),
+        }
+    }
+
     fn parse_array(
         &mut self,
         min_indent: usize,
-        mut initial_leading_comment: Option<String>,
+        mut initial_leading_comment: Option<Comment>,
     ) -> Result<YamlValue, String> {
         let mut items = Vec::new();
         let mut first_item = true;
+        let mut sequence_column = None;
 
         while let Some(_token) = self.current_token() {
             // Handle any leading comments before the array item
-            let leading_comment: Option<String>;
+            let leading_comment: Option<Comment>;
+            let item_start = self.current;
 
             // Use initial comment for first item if provided
             if first_item {
@@ -476,23 +804,43 @@ impl<'g> Parser<'g> {
                 leading_comment = self.collect_consecutive_comments();
             }
 
-            // After handling comments, check if we have a hyphen
-            let Some(token) = self.current_token() else {
+            // After handling comments, check if we have a hyphen in this
+            // sequence's column. Comments after the last item belong to
+            // whatever follows the array
+            let Some(hyphen_column) = self
+                .current_token()
+                .filter(|token| token.kind == TokenKind::Hyphen)
+                .map(|token| token.column)
+                .filter(|column| *sequence_column.get_or_insert(*column) == *column)
+            else {
+                self.current = item_start;
                 break;
             };
-            if token.kind != TokenKind::Hyphen {
-                break;
+            if items.is_empty() {
+                self.blocks.push(hyphen_column);
             }
 
             self.advance(); // consume hyphen
-            self.skip_whitespace();
-
-            let mut item = self.parse_value(min_indent)?;
+            let properties = self.take_properties()?;
+
+            // A block scalar item starts with | (literal) or > (folded)
+            let block_scalar = self.current_token().is_some_and(|token| {
+                matches!(token.kind, TokenKind::Pipe | TokenKind::GreaterThan)
+            });
+            let mut item = if block_scalar {
+                self.parse_multiline_string(hyphen_column)?
+            } else if self.at_empty_node(hyphen_column, false) {
+                self.empty_node()
+            } else {
+                self.parse_value(min_indent)?
+            };
+            self.apply(properties, &mut item);
+            item.layout.column = Some(hyphen_column);
 
             // Apply leading comment to the item if we collected one
             // The comment before the hyphen takes precedence
-            if leading_comment.is_some() {
-                item.leading_comment = leading_comment;
+            if let Some(comment) = leading_comment {
+                self.attach_comment(&mut item, comment, items.last());
             }
 
             items.push(item);
@@ -503,8 +851,8 @@ impl<'g> Parser<'g> {
             };
 
             match token.kind {
-                TokenKind::NewLine => {
-                    self.advance();
+                // A block scalar item stops at the dedent after its last line
+                TokenKind::NewLine | TokenKind::Dedent => {
                     self.skip_whitespace_and_newlines();
                 }
                 TokenKind::Hyphen => {
@@ -516,38 +864,38 @@ impl<'g> Parser<'g> {
                 | TokenKind::Whitespace
                 | TokenKind::Comment
                 | TokenKind::Indent
-                | TokenKind::Dedent
                 | TokenKind::Pipe
-                | TokenKind::GreaterThan => break,
+                | TokenKind::GreaterThan
+                | TokenKind::Flow
+                | TokenKind::Anchor
+                | TokenKind::Tag
+                | TokenKind::Alias => break,
             }
         }
 
+        if !items.is_empty() {
+            self.blocks.pop();
+        }
         Ok(YamlValue::Array(items))
     }
 
-    fn parse_multiline_string(
-        &mut self,
-        base_indent: usize,
-        is_literal: bool,
-    ) -> Result<YamlNode, String> {
-        // Skip any remaining whitespace and comments on the same line
-        self.skip_whitespace();
+    /// A block scalar, starting at its `|` or `>` header. The lexer has
+    /// already taken the content indentation, from the header's indentation
+    /// indicator or the first line, off each line.
+    fn parse_multiline_string(&mut self, base_indent: usize) -> Result<YamlNode, String> {
+        let Some(header) = self.advance() else {
+            return Err("Expected block scalar header".to_string());
+        };
+        let is_literal = header.kind == TokenKind::Pipe;
 
         // Handle optional chomping indicator (-, +, or none)
-        let mut chomp_mode = ChompMode::Clip; // default
-        if let Some(token) = self.current_token() {
-            match token.text {
-                "-" => {
-                    chomp_mode = ChompMode::Strip;
-                    self.advance();
-                }
-                "+" => {
-                    chomp_mode = ChompMode::Keep;
-                    self.advance();
-                }
-                _ => {}
-            }
-        }
+        let chomp_mode = if header.text.contains('-') {
+            ChompMode::Strip
+        } else if header.text.contains('+') {
+            ChompMode::Keep
+        } else {
+            ChompMode::Clip
+        };
 
         // Skip to next line
         while let Some(token) = self.current_token() {
@@ -601,7 +949,11 @@ impl<'g> Parser<'g> {
                 | TokenKind::Comment
                 | TokenKind::Dedent
                 | TokenKind::Pipe
-                | TokenKind::GreaterThan => {}
+                | TokenKind::GreaterThan
+                | TokenKind::Flow
+                | TokenKind::Anchor
+                | TokenKind::Tag
+                | TokenKind::Alias => {}
             }
 
             // Check indentation
@@ -638,85 +990,35 @@ impl<'g> Parser<'g> {
             }
         }
 
-        // Process the lines based on mode
-        let result = if is_literal {
+        // Lines after the last one with content are trailing blank lines,
+        // which only keep chomping holds on to
+        let content_end = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |last| last + 1);
+        let (content, trailing) = lines.split_at(content_end);
+        let mut result = if is_literal {
             // Literal mode: preserve line breaks
-            let mut result = lines
-                .iter()
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            // Apply chomping
-            match chomp_mode {
-                ChompMode::Strip => {
-                    // Remove all trailing newlines
-                    while result.ends_with('\n') {
-                        result.pop();
-                    }
-                }
-                ChompMode::Clip => {
-                    // Keep single trailing newline (default)
-                    while result.ends_with("\n\n") {
-                        result.pop();
-                    }
-                    if !result.ends_with('\n') && !result.is_empty() {
-                        result.push('\n');
-                    }
-                }
-                ChompMode::Keep => {
-                    // Keep all trailing newlines
-                    result.push('\n');
-                }
-            }
-
-            result
+            content.join("\n")
         } else {
-            // Folded mode: fold lines together
-            let mut result = String::new();
-            let mut prev_empty = false;
-
-            for (i, line) in lines.iter().enumerate() {
-                if line.is_empty() {
-                    if !prev_empty && i > 0 {
-                        result.push('\n');
-                    }
-                    prev_empty = true;
-                } else {
-                    if i > 0 && !prev_empty {
-                        result.push(' ');
-                    }
-                    result.push_str(line.trim_start());
-                    prev_empty = false;
-                }
-            }
+            fold_block_lines(content)
+        };
 
-            // Apply chomping
-            match chomp_mode {
-                ChompMode::Strip => {
-                    while result.ends_with('\n') || result.ends_with(' ') {
-                        result.pop();
-                    }
-                }
-                ChompMode::Clip => {
-                    while result.ends_with('\n') || result.ends_with(' ') {
-                        result.pop();
-                    }
-                    // Add single trailing newline for Clip mode
-                    if !result.is_empty() {
-                        result.push('\n');
-                    }
+        // Apply chomping
+        match chomp_mode {
+            ChompMode::Strip => {}
+            ChompMode::Clip => {
+                if !content.is_empty() {
+                    result.push('\n');
                 }
-                ChompMode::Keep => {
-                    // Keep trailing whitespace
-                    if !result.is_empty() && !result.ends_with('\n') {
-                        result.push('\n');
-                    }
+            }
+            ChompMode::Keep => {
+                if !content.is_empty() {
+                    result.push('\n');
                 }
+                result.extend(std::iter::repeat('\n').take(trailing.len()));
             }
-
-            result
-        };
+        }
 
         Ok(YamlNode::from_value(YamlValue::String(result)))
     }
@@ -724,7 +1026,7 @@ impl<'g> Parser<'g> {
     fn parse_object(
         &mut self,
         min_indent: usize,
-        mut initial_leading_comment: Option<String>,
+        mut initial_leading_comment: Option<Comment>,
     ) -> Result<YamlNode, String> {
         let mut object = YamlObject::new();
         let mut first_key = true;
@@ -760,6 +1062,9 @@ impl<'g> Parser<'g> {
             let key_column = token.column;
             let key = token.text.to_string();
             self.advance();
+            if object.is_empty() {
+                self.blocks.push(key_column);
+            }
 
             self.skip_whitespace();
 
@@ -776,69 +1081,180 @@ impl<'g> Parser<'g> {
 
             // Skip whitespace after colon
             self.skip_whitespace();
+            let properties = self.take_properties()?;
 
-            // Collect the value - could be multiple tokens on the same line
-            let Some(token) = self.current_token() else {
-                return Err("Expected value after colon".to_string());
+            // `key: # comment` followed by an indented block: the comment
+            // belongs to the block
+            let block_comment = if self.at_comment_before_block() {
+                self.collect_comment()
+            } else {
+                None
             };
 
-            let mut value = match token.kind {
-                TokenKind::Pipe => {
-                    // Literal multiline string indicator
-                    self.advance(); // consume |
-                    self.parse_multiline_string(key_column, true)?
-                }
-                TokenKind::GreaterThan => {
-                    // Folded multiline string indicator
-                    self.advance(); // consume >
-                    self.parse_multiline_string(key_column, false)?
-                }
-                TokenKind::NewLine | TokenKind::Indent => {
-                    // Value is on next line
-                    self.skip_whitespace_and_newlines();
-                    // Use key_column as the new min_indent for nested values
-                    self.parse_value(key_column)?
-                }
-                TokenKind::Identifier
-                | TokenKind::Colon
-                | TokenKind::String
-                | TokenKind::Whitespace
-                | TokenKind::Hyphen
-                | TokenKind::Comment
-                | TokenKind::Dedent => {
-                    // Value is on same line - collect until newline
-                    self.parse_inline_value()?
+            let empty = self.at_empty_node(key_column, true);
+            let mut value = if empty {
+                self.empty_node()
+            } else {
+                // Collect the value - could be multiple tokens on the same line
+                let Some(token) = self.current_token() else {
+                    return Err("Expected value after colon".to_string());
+                };
+
+                match token.kind {
+                    TokenKind::Pipe | TokenKind::GreaterThan => {
+                        // Literal or folded multiline string
+                        self.parse_multiline_string(key_column)?
+                    }
+                    TokenKind::NewLine | TokenKind::Indent => {
+                        // Value is on next line
+                        self.skip_whitespace_and_newlines();
+                        // Use key_column as the new min_indent for nested values
+                        self.parse_value(key_column)?
+                    }
+                    TokenKind::Identifier
+                    | TokenKind::Colon
+                    | TokenKind::String
+                    | TokenKind::Whitespace
+                    | TokenKind::Hyphen
+                    | TokenKind::Comment
+                    | TokenKind::Dedent
+                    | TokenKind::Flow
+                    | TokenKind::Anchor
+                    | TokenKind::Tag
+                    | TokenKind::Alias => {
+                        // Value is on same line - collect until newline
+                        self.parse_inline_value(key_column)?
+                    }
                 }
             };
+            self.apply(properties, &mut value);
+            value.layout.column = Some(key_column);
 
             // Apply leading comment to the value node if we collected one
             // The comment before the key takes precedence over any comment in the value
-            if leading_comment.is_some() {
-                value.leading_comment = leading_comment;
+            if let Some(comment) = leading_comment {
+                self.attach_comment(&mut value, comment, object.values().last());
+            }
+            if block_comment.is_some() {
+                value.inline_comment = block_comment;
             }
 
             object.insert(key, value);
 
             self.skip_whitespace();
+            // A block scalar has already consumed its final line break and
+            // stops at the dedent; the next key's column decides where it goes
             if let Some(token) = self.current_token() {
-                if token.kind == TokenKind::NewLine {
-                    self.advance();
+                if matches!(token.kind, TokenKind::NewLine | TokenKind::Dedent) {
                     self.skip_whitespace_and_newlines();
                 }
             }
+        }
 
-            // Check if we've dedented or reached end
-            let Some(token) = self.current_token() else {
-                break;
-            };
-            if token.kind == TokenKind::Dedent {
-                self.advance();
-                break;
+        if !object.is_empty() {
+            self.blocks.pop();
+        }
+        Ok(YamlNode::from_value(YamlValue::Object(object)))
+    }
+}
+
+/// The block a comment line at `column` belongs to, as a level kept in
+/// `Layout::comment_levels`. `blocks` holds the entry columns of the open
+/// block collections, innermost last, and `previous` is the entry before
+/// the line in the innermost one.
+fn comment_level(column: usize, blocks: &[usize], previous: Option<&YamlNode>) -> isize {
+    let Some((&own, outer)) = blocks.split_last() else {
+        return 0;
+    };
+    if column < own {
+        // The nearest enclosing block the line isn't to the left of
+        let up = outer
+            .iter()
+            .rev()
+            .position(|&block| block <= column)
+            .map_or(outer.len(), |index| index + 1);
+        return -(up as isize);
+    }
+    // Otherwise the deepest block ending at `previous` the line lines up
+    // with or is indented into. Ties go to the outer block.
+    let mut level = 0;
+    let mut matched = own;
+    let mut node = previous;
+    let mut depth = 0;
+    while let Some(entry) = node.and_then(last_entry) {
+        let Some(block) = entry.layout.column.filter(|&block| block <= column) else {
+            break;
+        };
+        depth += 1;
+        if block > matched {
+            level = depth;
+            matched = block;
+        }
+        node = Some(entry);
+    }
+    level
+}
+
+/// The last entry of a collection
+fn last_entry(node: &YamlNode) -> Option<&YamlNode> {
+    match &node.value {
+        YamlValue::Array(items) => items.last(),
+        YamlValue::Object(obj) => obj.values().last(),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => None,
+    }
+}
+
+/// Fold the content lines of a `>` block scalar. A line break between two
+/// lines becomes a space, unless blank lines follow it, which each become a
+/// newline instead. Breaks next to more-indented lines are all kept.
+fn fold_block_lines(lines: &[String]) -> String {
+    let spaced = |line: &str| line.starts_with([' ', '\t']);
+    let mut result = String::new();
+    let mut previous: Option<&str> = None;
+    let mut breaks = 0;
+    for line in lines {
+        if line.is_empty() {
+            breaks += 1;
+            continue;
+        }
+        match previous {
+            Some(previous) if spaced(previous) || spaced(line) => {
+                result.extend(std::iter::repeat('\n').take(breaks + 1));
             }
+            Some(_) if breaks == 0 => result.push(' '),
+            _ => result.extend(std::iter::repeat('\n').take(breaks)),
         }
+        result.push_str(line);
+        previous = Some(line);
+        breaks = 0;
+    }
+    result
+}
 
-        Ok(YamlNode::from_value(YamlValue::Object(object)))
+/// The content of a string token, without its quotes and with the lines of
+/// a quoted string folded. The token runs to the end of the input if its
+/// closing quote is missing.
+fn unquote(text: &str) -> Result<String, String> {
+    let Some(quote @ ('"' | '\'')) = text.chars().next() else {
+        return Ok(text.to_string());
+    };
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' && quote == '"' {
+            chars.next();
+        } else if c == quote {
+            // `''` is an escaped quote in a single-quoted string
+            if quote == '\'' && text[index + 1..].starts_with('\'') {
+                chars.next();
+                continue;
+            }
+            if index + 1 == text.len() {
+                return Ok(flow::fold_quoted(&text[1..index], quote));
+            }
+            break;
+        }
     }
+    Err("Unterminated quoted string".to_string())
 }
 
 #[cfg(test)]
@@ -0,0 +1,96 @@
+//! Dotted paths used to address nodes inside a tree.
+//!
+//! A path is a list of segments separated by `.`, e.g. `server.host` or
+//! `features.0.name`. Segments select keys in objects and positions in
+//! arrays. The empty path refers to the root node. A key containing a `.`
+//! is written in double quotes, as in `labels."app.kubernetes.io/name"`,
+//! with `\"` and `\\` for a quote or backslash inside.
+
+use std::borrow::Cow;
+
+pub(crate) fn segments(path: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    split(path).into_iter().map(|(_, segment)| segment)
+}
+
+pub(crate) fn join(parent: &str, segment: &str) -> String {
+    let segment = quote(segment);
+    if parent.is_empty() {
+        segment.into_owned()
+    } else {
+        format!("{}.{}", parent, segment)
+    }
+}
+
+/// Split a path into its parent path and last segment.
+pub(crate) fn split_last(path: &str) -> Option<(&str, Cow<'_, str>)> {
+    let (start, last) = split(path).pop()?;
+    Some((path[..start].trim_end_matches('.'), last))
+}
+
+/// The same path written in the form [`join`] produces
+pub(crate) fn normalize(path: &str) -> String {
+    segments(path).fold(String::new(), |parent, segment| join(&parent, &segment))
+}
+
+/// Match `path` against the start of `pattern`, where a `*` segment matches
+/// any key or index. Returns how many pattern segments are left over.
+pub(crate) fn match_prefix(pattern: &str, path: &[String]) -> Option<usize> {
+    let mut pattern = segments(pattern);
+    for segment in path {
+        match pattern.next() {
+            Some(expected) if expected == "*" || expected == segment.as_str() => {}
+            _ => return None,
+        }
+    }
+    Some(pattern.count())
+}
+
+/// The non-empty segments of a path, with the offset each starts at
+fn split(path: &str) -> Vec<(usize, Cow<'_, str>)> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start < path.len() {
+        let rest = &path[start..];
+        let (segment, len) = match rest.strip_prefix('"') {
+            Some(quoted) => unquote(quoted),
+            None => {
+                let end = rest.find('.').unwrap_or(rest.len());
+                (Cow::Borrowed(&rest[..end]), end)
+            }
+        };
+        if !segment.is_empty() || rest.starts_with('"') {
+            found.push((start, segment));
+        }
+        // Move past the `.` after the segment
+        start += len;
+        start += path[start..]
+            .find('.')
+            .map_or(path.len() - start, |dot| dot + 1);
+    }
+    found
+}
+
+/// Read a quoted segment after its opening quote. Returns the key and the
+/// length of the segment, quotes included.
+fn unquote(quoted: &str) -> (Cow<'_, str>, usize) {
+    let mut key = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return (Cow::Owned(key), index + 2),
+            '\\' => key.extend(chars.next().map(|(_, escaped)| escaped)),
+            c => key.push(c),
+        }
+    }
+    // An unterminated quote runs to the end of the path
+    (Cow::Owned(key), quoted.len() + 1)
+}
+
+fn quote(segment: &str) -> Cow<'_, str> {
+    if segment.is_empty() || segment.contains('.') || segment.starts_with('"') {
+        let escaped = segment.replace('\\', "\\\\").replace('"', "\\\"");
+        Cow::Owned(format!("\"{}\"", escaped))
+    } else {
+        Cow::Borrowed(segment)
+    }
+}
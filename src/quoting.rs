@@ -0,0 +1,162 @@
+//! Policies deciding which plain strings the emitter quotes.
+//!
+//! Whatever the policy, strings that would not parse back as the same
+//! plain scalar (empty strings, `key: value`-like text, comments, leading
+//! indicators, ...) are always quoted.
+
+use std::fmt;
+
+/// Decides whether a string that could be written plain is quoted anyway,
+/// usually because other YAML tools would read it as a number, boolean or
+/// null.
+///
+/// # Example
+///
+/// ```rust
+/// use yamp::{emit_with, parse, EmitOptions, QuotingPolicy};
+///
+/// /// Quote anything that looks like a version number
+/// #[derive(Debug)]
+/// struct Versions;
+///
+/// impl QuotingPolicy for Versions {
+///     fn needs_quotes(&self, value: &str) -> bool {
+///         value.starts_with(|c: char| c.is_ascii_digit()) && value.contains('.')
+///     }
+/// }
+///
+/// let parsed = parse("name: api\nversion: 1.10").unwrap();
+/// let options = EmitOptions::new().with_quoting(Versions);
+/// assert_eq!(emit_with(&parsed, &options), "name: api\nversion: \"1.10\"");
+/// ```
+pub trait QuotingPolicy: fmt::Debug + Send + Sync {
+    fn needs_quotes(&self, value: &str) -> bool;
+}
+
+/// The default policy: quotes `true`, `false`, `null`, anything that parses
+/// as a number, numbers with leading zeros, values starting with `-`, and
+/// values containing YAML indicator characters or `~`.
+///
+/// YAML 1.1 booleans such as `yes` and `off` stay plain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Standard;
+
+impl QuotingPolicy for Standard {
+    fn needs_quotes(&self, s: &str) -> bool {
+        matches!(s, "true" | "false" | "null")
+            || s.contains(|c| ":#[]{},&*!|>'\"%@`~".contains(c))
+            || s.starts_with('-')
+            || s.parse::<f64>().is_ok()
+            // Quote leading zeros to preserve them
+            || has_leading_zero(s)
+    }
+}
+
+/// Quotes only what has to be quoted to parse back unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Minimal;
+
+impl QuotingPolicy for Minimal {
+    fn needs_quotes(&self, _value: &str) -> bool {
+        false
+    }
+}
+
+/// [`Standard`], plus every string a YAML 1.1 parser would read as
+/// something else: `yes`/`no`/`on`/`off`/`y`/`n` and `true`/`false`/`null`
+/// in any case, `~`, hex, octal and binary integers, numbers with `_`
+/// separators, `.inf`/`.nan` and dates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Conservative;
+
+impl QuotingPolicy for Conservative {
+    fn needs_quotes(&self, s: &str) -> bool {
+        Standard.needs_quotes(s) || is_yaml11_keyword(s) || is_yaml11_number(s) || is_date(s)
+    }
+}
+
+/// Quotes every string.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysQuote;
+
+impl QuotingPolicy for AlwaysQuote {
+    fn needs_quotes(&self, _value: &str) -> bool {
+        true
+    }
+}
+
+/// Whether a plain scalar would read back as something other than `s`.
+pub(crate) fn requires_quotes(s: &str) -> bool {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return true;
+    };
+    let starts_badly = match first {
+        // `- x` is a sequence entry, `--` starts a document marker
+        '-' => chars
+            .next()
+            .map_or(true, |second| matches!(second, ' ' | '\t' | '-')),
+        _ => "?:,[]{}#&*!|>'\"%@`".contains(first),
+    };
+    starts_badly
+        || s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace)
+        || s.chars().any(char::is_control)
+        || s.contains('#')
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(":\t")
+        || s.contains(" :")
+        || s.contains("\t:")
+}
+
+/// Like [`requires_quotes`], for scalars inside `[...]` or `{...}`.
+pub(crate) fn requires_quotes_in_flow(s: &str) -> bool {
+    requires_quotes(s) || s.contains([',', '[', ']', '{', '}'])
+}
+
+fn has_leading_zero(s: &str) -> bool {
+    s.len() > 1 && s.starts_with('0') && s.chars().nth(1).is_some_and(|c| c.is_ascii_digit())
+}
+
+fn is_yaml11_keyword(s: &str) -> bool {
+    matches!(
+        s.to_ascii_lowercase().as_str(),
+        "y" | "n" | "yes" | "no" | "on" | "off" | "true" | "false" | "null" | "~"
+    )
+}
+
+fn is_yaml11_number(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let lower = unsigned.to_ascii_lowercase();
+    let radix = |prefix: &str, is_digit: fn(&char) -> bool| {
+        lower.strip_prefix(prefix).is_some_and(|digits| {
+            !digits.is_empty() && digits.chars().all(|c| c == '_' || is_digit(&c))
+        })
+    };
+    matches!(lower.as_str(), ".inf" | ".nan")
+        || radix("0x", char::is_ascii_hexdigit)
+        || radix("0o", |c| ('0'..='7').contains(c))
+        || radix("0b", |c| matches!(c, '0' | '1'))
+        // 1_000 and 1_000.5
+        || (unsigned.starts_with(|c: char| c.is_ascii_digit())
+            && unsigned.replace('_', "").parse::<f64>().is_ok())
+}
+
+/// `2024-01-31`, optionally followed by a time
+fn is_date(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        bytes
+            .get(range)
+            .is_some_and(|part| part.iter().all(u8::is_ascii_digit))
+    };
+    digits(0..4)
+        && bytes.get(4) == Some(&b'-')
+        && digits(5..7)
+        && bytes.get(7) == Some(&b'-')
+        && digits(8..10)
+        && bytes
+            .get(10)
+            .map_or(true, |&b| matches!(b, b'T' | b't' | b' '))
+}
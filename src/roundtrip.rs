@@ -0,0 +1,165 @@
+//! Checking that emitted YAML parses back to the tree it came from.
+
+use crate::options::{AliasMode, EmitOptions, ParseOptions};
+use crate::path;
+use crate::types::{YamlNode, YamlValue};
+use std::fmt;
+
+/// Emitted YAML that would not parse back to the same tree, from
+/// [`emit_checked`](crate::emit_checked).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTripError {
+    /// Dotted path of the first node that differs, empty for the root
+    pub path: String,
+    /// What differs there
+    pub reason: String,
+    /// The YAML that was emitted
+    pub output: String,
+}
+
+impl fmt::Display for RoundTripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "output doesn't round-trip: {}", self.reason)
+        } else {
+            write!(
+                f,
+                "output doesn't round-trip at `{}`: {}",
+                self.path, self.reason
+            )
+        }
+    }
+}
+
+impl std::error::Error for RoundTripError {}
+
+/// Parse `output` and compare it with `expected`, as far as `options` are
+/// meant to keep it: key order is ignored in canonical mode and comments
+/// when they aren't written.
+pub(crate) fn check(
+    expected: &YamlNode,
+    output: String,
+    options: &EmitOptions,
+) -> Result<String, RoundTripError> {
+    // Anchors and aliases in the tree are written back as they are
+    let parse_options = ParseOptions::new().with_aliases(AliasMode::Preserve);
+    let difference = match crate::parse_with(&output, &parse_options) {
+        Ok(found) => Comparison { options }.node(expected, &found, ""),
+        Err(error) => Some((String::new(), format!("doesn't parse: {}", error))),
+    };
+    match difference {
+        Some((path, reason)) => Err(RoundTripError {
+            path,
+            reason,
+            output,
+        }),
+        None => Ok(output),
+    }
+}
+
+struct Comparison<'o> {
+    options: &'o EmitOptions,
+}
+
+impl Comparison<'_> {
+    /// The path and description of the first difference, if any
+    fn node(&self, expected: &YamlNode, found: &YamlNode, at: &str) -> Option<(String, String)> {
+        if self.options.comments {
+            let comments = [
+                ("leading", &expected.leading_comment, &found.leading_comment),
+                ("inline", &expected.inline_comment, &found.inline_comment),
+            ];
+            for (slot, expected, found) in comments {
+                if words(expected) != words(found) {
+                    let reason =
+                        format!("{} comment {:?} came back as {:?}", slot, expected, found);
+                    return Some((at.to_string(), reason));
+                }
+            }
+        }
+
+        let properties = [
+            ("anchor", &expected.anchor, &found.anchor),
+            ("tag", &expected.tag, &found.tag),
+        ];
+        for (property, expected, found) in properties {
+            if expected != found {
+                let reason = format!("{} {:?} came back as {:?}", property, expected, found);
+                return Some((at.to_string(), reason));
+            }
+        }
+
+        match (&expected.value, &found.value) {
+            (YamlValue::String(expected), YamlValue::String(found))
+            | (YamlValue::Alias(expected), YamlValue::Alias(found)) => {
+                (expected != found).then(|| {
+                    (
+                        at.to_string(),
+                        format!("{:?} came back as {:?}", expected, found),
+                    )
+                })
+            }
+            (YamlValue::Array(expected), YamlValue::Array(found)) => {
+                for (index, expected) in expected.iter().enumerate() {
+                    let item = path::join(at, &index.to_string());
+                    let Some(found) = found.get(index) else {
+                        return Some((item, "item is missing".to_string()));
+                    };
+                    if let Some(difference) = self.node(expected, found, &item) {
+                        return Some(difference);
+                    }
+                }
+                (found.len() > expected.len()).then(|| {
+                    let item = path::join(at, &expected.len().to_string());
+                    (item, "unexpected item".to_string())
+                })
+            }
+            (YamlValue::Object(expected), YamlValue::Object(found)) => {
+                let mut found_pairs = found.iter();
+                for (key, expected) in expected.iter() {
+                    let pair = path::join(at, key);
+                    let in_place = found_pairs
+                        .next()
+                        .filter(|(found_key, _)| *found_key == key);
+                    let found_value = match (in_place, found.get(key)) {
+                        (Some((_, value)), _) => value,
+                        (None, None) => return Some((pair, "key is missing".to_string())),
+                        (None, Some(_)) if !self.options.canonical => {
+                            return Some((pair, "key moved".to_string()))
+                        }
+                        (None, Some(value)) => value,
+                    };
+                    if let Some(difference) = self.node(expected, found_value, &pair) {
+                        return Some(difference);
+                    }
+                }
+                found
+                    .keys()
+                    .find(|key| expected.get(key).is_none())
+                    .map(|key| (path::join(at, key), "unexpected key".to_string()))
+            }
+            (YamlValue::Blank, YamlValue::Blank) => None,
+            (expected, found) => Some((
+                at.to_string(),
+                format!("{} came back as {}", kind(expected), kind(found)),
+            )),
+        }
+    }
+}
+
+/// Comments are compared word by word, so rewrapping isn't a difference
+fn words(comment: &Option<String>) -> Vec<&str> {
+    comment
+        .as_deref()
+        .map_or_else(Vec::new, |comment| comment.split_whitespace().collect())
+}
+
+fn kind(value: &YamlValue) -> &'static str {
+    match value {
+        YamlValue::String(_) => "a string",
+        YamlValue::Array(_) => "a sequence",
+        YamlValue::Object(_) => "a mapping",
+        YamlValue::Alias(_) => "an alias",
+        YamlValue::Blank => "a blank node",
+    }
+}
@@ -1,4 +1,6 @@
-use std::collections::BTreeMap;
+use crate::path;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 
 /// Represents a YAML value with associated comments
 ///
@@ -11,6 +13,62 @@ pub struct YamlNode {
     pub value: YamlValue,
     pub leading_comment: Option<String>,
     pub inline_comment: Option<String>,
+    /// How a collection is laid out when emitted. Ignored for strings.
+    pub style: CollectionStyle,
+    /// The `&name` anchor on this node, kept when parsing with
+    /// `AliasMode::Preserve`
+    pub anchor: Option<String>,
+    /// The tag on this node, such as `!Ref` or `!!str`, exactly as written.
+    /// Tags are kept but never interpreted; the value is still a string or
+    /// collection.
+    pub tag: Option<String>,
+    pub(crate) layout: Layout,
+}
+
+/// Where a node and its comment lines sit in the block structure. It only
+/// affects where comments are written, so it's ignored when comparing nodes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layout {
+    /// Column of the node's key or `-` in the parsed text
+    pub(crate) column: Option<usize>,
+    /// Block each leading comment line belongs to, relative to the block
+    /// holding the node: 0 for that block, `k` for the block `k` levels
+    /// down the end of the entry before the node, `-k` for `k` levels up.
+    /// Lines after the last entry of a nested block are kept this way.
+    pub(crate) comment_levels: Vec<isize>,
+    /// The same for the trailing comment of a root node, where 0 is the
+    /// root's own block and `k` is `k` levels down the end of its last entry
+    pub(crate) trailing_levels: Vec<isize>,
+}
+
+impl PartialEq for Layout {
+    fn eq(&self, _: &Layout) -> bool {
+        true
+    }
+}
+
+impl Layout {
+    /// The level of each line of `comment`, all 0 unless recorded for it
+    pub(crate) fn levels(recorded: &[isize], comment: Option<&str>) -> Vec<isize> {
+        let lines = comment.map_or(0, |comment| comment.lines().count());
+        if recorded.len() == lines {
+            recorded.to_vec()
+        } else {
+            vec![0; lines]
+        }
+    }
+}
+
+/// Layout of an array or object in emitted YAML
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollectionStyle {
+    /// Decided by the emit options
+    #[default]
+    Auto,
+    /// One `- item` or `key: value` per line
+    Block,
+    /// `[a, b]` or `{k: v}` on one line, unless an element has comments
+    Flow,
 }
 
 /// Order-preserving YAML object structure
@@ -25,6 +83,11 @@ pub enum YamlValue {
     String(String),
     Array(Vec<YamlNode>),
     Object(YamlObject),
+    /// A `*name` reference to an anchored node, kept as written
+    Alias(String),
+    /// Nothing written for the node, as in `key:` or a bare `-`. Unlike
+    /// `""`, `[]` or `{}`, it isn't read as null or any other value.
+    Blank,
 }
 
 impl YamlNode {
@@ -33,11 +96,16 @@ impl YamlNode {
             value,
             leading_comment: None,
             inline_comment: None,
+            style: CollectionStyle::Auto,
+            anchor: None,
+            tag: None,
+            layout: Layout::default(),
         }
     }
 
     pub fn with_leading_comment<S: Into<String>>(mut self, comment: S) -> Self {
         self.leading_comment = Some(comment.into());
+        self.layout.comment_levels.clear();
         self
     }
 
@@ -46,6 +114,31 @@ impl YamlNode {
         self
     }
 
+    pub fn with_style(mut self, style: CollectionStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_anchor<S: Into<String>>(mut self, name: S) -> Self {
+        self.anchor = Some(name.into());
+        self
+    }
+
+    /// Set the tag, written with its `!`, e.g. `"!Ref"` or `"!!binary"`
+    pub fn with_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// A `*name` alias node
+    pub fn alias<S: Into<String>>(name: S) -> Self {
+        YamlNode::from_value(YamlValue::Alias(name.into()))
+    }
+
+    /// A node with nothing written for it
+    pub fn blank() -> Self {
+        YamlNode::from_value(YamlValue::Blank)
+    }
 
     pub fn as_str(&self) -> Option<&str> {
         match &self.value {
@@ -54,6 +147,14 @@ impl YamlNode {
         }
     }
 
+    /// The anchor name an alias node refers to
+    pub fn as_alias(&self) -> Option<&str> {
+        match &self.value {
+            YamlValue::Alias(name) => Some(name),
+            _ => None,
+        }
+    }
+
     pub fn as_array(&self) -> Option<&Vec<YamlNode>> {
         match &self.value {
             YamlValue::Array(arr) => Some(arr),
@@ -82,6 +183,35 @@ impl YamlNode {
         }
     }
 
+    /// Look up a node by dotted path, e.g. `"server.host"` or `"features.0"`.
+    /// A key containing a dot is quoted, as in `labels."app.kubernetes.io/name"`.
+    ///
+    /// Segments select keys in objects and indices in arrays. The empty path
+    /// refers to this node.
+    pub fn get_path(&self, path: &str) -> Option<&YamlNode> {
+        let mut node = self;
+        for segment in path::segments(path) {
+            node = match &node.value {
+                YamlValue::Object(obj) => obj.get(&segment)?,
+                YamlValue::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+                YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => return None,
+            };
+        }
+        Some(node)
+    }
+
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut YamlNode> {
+        let mut node = self;
+        for segment in path::segments(path) {
+            node = match &mut node.value {
+                YamlValue::Object(obj) => obj.get_mut(&segment)?,
+                YamlValue::Array(items) => items.get_mut(segment.parse::<usize>().ok()?)?,
+                YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => return None,
+            };
+        }
+        Some(node)
+    }
+
     pub fn is_string(&self) -> bool {
         matches!(&self.value, YamlValue::String(_))
     }
@@ -93,6 +223,32 @@ impl YamlNode {
     pub fn is_object(&self) -> bool {
         matches!(&self.value, YamlValue::Object(_))
     }
+
+    pub fn is_alias(&self) -> bool {
+        matches!(&self.value, YamlValue::Alias(_))
+    }
+
+    pub fn is_blank(&self) -> bool {
+        matches!(&self.value, YamlValue::Blank)
+    }
+
+    /// Anchored nodes in this tree, in document order.
+    pub fn anchors(&self) -> Vec<(&str, &YamlNode)> {
+        let mut found = Vec::new();
+        collect_anchors(self, &mut found);
+        found
+    }
+
+    /// The node the alias at `path` refers to: the nearest node before it
+    /// with that anchor, as a parser would resolve it. Returns `None` if
+    /// there is no alias at `path` or it has no anchor.
+    pub fn resolve_alias(&self, path: &str) -> Option<&YamlNode> {
+        let name = self.get_path(path)?.as_alias()?;
+        let mut anchors = HashMap::new();
+        let segments: Vec<_> = path::segments(path).collect();
+        collect_anchors_before(self, &segments, &mut anchors);
+        anchors.get(name).copied()
+    }
 }
 
 // Internal methods for YamlNode
@@ -106,10 +262,67 @@ impl YamlNode {
             value,
             leading_comment: leading,
             inline_comment: inline,
+            style: CollectionStyle::Auto,
+            anchor: None,
+            tag: None,
+            layout: Layout::default(),
         }
     }
 }
 
+fn collect_anchors<'a>(node: &'a YamlNode, found: &mut Vec<(&'a str, &'a YamlNode)>) {
+    if let Some(anchor) = &node.anchor {
+        found.push((anchor, node));
+    }
+    match &node.value {
+        YamlValue::Array(items) => items.iter().for_each(|item| collect_anchors(item, found)),
+        YamlValue::Object(obj) => obj.values().for_each(|value| collect_anchors(value, found)),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+    }
+}
+
+/// Anchors defined before the node at `path`, each name holding its latest
+/// node. A node's anchor counts once the whole node is written, so an alias
+/// can't refer to a node it is inside of.
+fn collect_anchors_before<'a>(
+    node: &'a YamlNode,
+    path: &[Cow<str>],
+    found: &mut HashMap<&'a str, &'a YamlNode>,
+) {
+    let Some((segment, rest)) = path.split_first() else {
+        return;
+    };
+    let (children, position): (Vec<&YamlNode>, Option<usize>) = match &node.value {
+        YamlValue::Array(items) => (items.iter().collect(), segment.parse().ok()),
+        YamlValue::Object(obj) => (obj.values().collect(), obj.position(segment)),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => return,
+    };
+    let Some(position) = position else {
+        return;
+    };
+    for child in children.iter().take(position) {
+        collect_complete_anchors(child, found);
+    }
+    if let Some(child) = children.get(position) {
+        collect_anchors_before(child, rest, found);
+    }
+}
+
+fn collect_complete_anchors<'a>(node: &'a YamlNode, found: &mut HashMap<&'a str, &'a YamlNode>) {
+    match &node.value {
+        YamlValue::Array(items) => items
+            .iter()
+            .for_each(|item| collect_complete_anchors(item, found)),
+        YamlValue::Object(obj) => obj
+            .values()
+            .for_each(|value| collect_complete_anchors(value, found)),
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Blank => {}
+    }
+    if let Some(anchor) = &node.anchor {
+        found.insert(anchor, node);
+    }
+}
+
 impl YamlObject {
     pub fn new() -> Self {
         YamlObject { pairs: Vec::new() }
@@ -144,6 +357,12 @@ impl YamlObject {
         None
     }
 
+    /// Remove a key, keeping the order of the remaining pairs.
+    pub fn remove(&mut self, key: &str) -> Option<YamlNode> {
+        let index = self.position(key)?;
+        Some(self.pairs.remove(index).1)
+    }
+
     pub fn get(&self, key: &str) -> Option<&YamlNode> {
         self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v)
     }
@@ -184,6 +403,21 @@ impl YamlObject {
     }
 }
 
+// Internal methods for YamlObject
+impl YamlObject {
+    pub(crate) fn position(&self, key: &str) -> Option<usize> {
+        self.pairs.iter().position(|(k, _)| k == key)
+    }
+
+    pub(crate) fn insert_at(&mut self, index: usize, key: String, value: YamlNode) {
+        self.pairs.insert(index, (key, value));
+    }
+
+    pub(crate) fn pair_at_mut(&mut self, index: usize) -> Option<(&String, &mut YamlNode)> {
+        self.pairs.get_mut(index).map(|(k, v)| (&*k, v))
+    }
+}
+
 impl Default for YamlObject {
     fn default() -> Self {
         Self::new()
@@ -9,7 +9,7 @@ fn test_simple_key_value() {
 
     let map = match &result.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected object at root, got {:?}", result.value)
         }
     };
@@ -19,7 +19,7 @@ fn test_simple_key_value() {
     let key_node = map.get("key").expect("key not found");
     let s = match &key_node.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected string value, got {:?}", key_node.value)
         }
     };
@@ -0,0 +1,130 @@
+#![deny(clippy::all)]
+
+use yamp::{parse, parse_documents_with, parse_with, AliasMode, CollectionStyle, ParseOptions};
+
+fn expand(max_expansion: usize) -> ParseOptions {
+    ParseOptions::new().with_aliases(AliasMode::Expand { max_expansion })
+}
+
+const COMPOSE: &str = r#"x-logging: &logging
+  driver: json-file
+  options: {max-size: 10m}
+x-ports: &ports ["80", "443"]
+services:
+  web:
+    image: &image nginx:1.25
+    logging: *logging # Shared
+    ports: *ports
+  proxy:
+    image: *image
+    logging: *logging
+"#;
+
+#[test]
+fn test_aliases_are_rejected_by_default() {
+    let error = parse(COMPOSE).unwrap_err();
+    assert!(error.contains("`&logging`"), "{}", error);
+    assert!(error.contains("not enabled"), "{}", error);
+
+    // `*` and `&` without a name are text
+    let parsed = parse("serve:\n  - *.html\n  - &.x").unwrap();
+    assert_eq!(
+        parsed.get_path("serve.0").and_then(|n| n.as_str()),
+        Some("*.html")
+    );
+    assert_eq!(
+        parsed.get_path("serve.1").and_then(|n| n.as_str()),
+        Some("&.x")
+    );
+}
+
+#[test]
+fn test_expand_aliases() {
+    let parsed = parse_with(COMPOSE, &expand(100)).unwrap();
+    let web = parsed.get_path("services.web").unwrap();
+    let proxy = parsed.get_path("services.proxy").unwrap();
+
+    assert_eq!(
+        web.get("logging"),
+        parsed
+            .get("x-logging")
+            .map(|l| {
+                let mut copy = l.clone();
+                copy.inline_comment = Some("Shared".to_string());
+                copy
+            })
+            .as_ref()
+    );
+    assert_eq!(
+        proxy
+            .get_path("logging.options.max-size")
+            .and_then(|n| n.as_str()),
+        Some("10m")
+    );
+    assert_eq!(
+        proxy.get("image").and_then(|n| n.as_str()),
+        Some("nginx:1.25")
+    );
+
+    // Copies keep the flow style of what they copy
+    let ports = web.get("ports").unwrap();
+    assert_eq!(ports.style, CollectionStyle::Flow);
+    assert_eq!(ports.as_array().map(Vec::len), Some(2));
+
+    // Aliases inside flow collections, and anchors on sequence items
+    let parsed = parse_with(
+        "- &greeting hello\n- [*greeting, {to: *greeting}]",
+        &expand(10),
+    )
+    .unwrap();
+    assert_eq!(
+        parsed.get_path("1.1.to").and_then(|n| n.as_str()),
+        Some("hello")
+    );
+}
+
+#[test]
+fn test_alias_errors() {
+    let options = expand(1000);
+    assert_eq!(
+        parse_with("a: *missing", &options).unwrap_err(),
+        "Alias *missing has no anchor"
+    );
+    assert_eq!(
+        parse_with("a: &a\n  b: *a", &options).unwrap_err(),
+        "Alias *a refers to a node containing it"
+    );
+
+    // Anchors belong to their own document
+    let error = parse_documents_with("a: &a x\n---\nb: *a\n", &options).unwrap_err();
+    assert_eq!(error, "Document 2: Alias *a has no anchor");
+}
+
+#[test]
+fn test_expansion_limits() {
+    // Each level holds ten copies of the one before
+    let mut laughs = String::from("l0: &l0 [lol, lol, lol, lol, lol, lol, lol, lol, lol, lol]\n");
+    for level in 1..10 {
+        let alias = format!("*l{}", level - 1);
+        let items = vec![alias.as_str(); 10].join(", ");
+        laughs.push_str(&format!("l{0}: &l{0} [{1}]\n", level, items));
+    }
+    let error = parse_with(&laughs, &expand(100_000)).unwrap_err();
+    assert!(
+        error.contains("over the limit of 100000 nodes"),
+        "{}",
+        error
+    );
+
+    let small = laughs.lines().take(2).collect::<Vec<_>>().join("\n");
+    assert!(parse_with(&small, &expand(1_000)).is_ok());
+    assert!(parse_with(&small, &expand(100)).is_err());
+
+    // Aliases nested through too many anchors
+    let mut chain = String::from("a0: &a0 x\n");
+    for level in 1..=70 {
+        chain.push_str(&format!("a{0}: &a{0} [*a{1}]\n", level, level - 1));
+    }
+    let error = parse_with(&chain, &expand(100_000)).unwrap_err();
+    assert!(error.contains("anchors deep"), "{}", error);
+}
@@ -0,0 +1,134 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit, emit_checked, emit_with, parse_with, AliasMode, EmitOptions, FlowStyle, ParseOptions,
+    YamlNode, YamlObject, YamlValue,
+};
+
+fn preserve() -> ParseOptions {
+    ParseOptions::new().with_aliases(AliasMode::Preserve)
+}
+
+const COMPOSE: &str = r#"x-logging: &logging
+  driver: json-file
+  options: {max-size: 10m}
+x-ports: &ports ["80", "443"]
+services:
+  web:
+    image: &image nginx # Pinned
+    logging: *logging # Shared
+    ports: *ports
+    volumes:
+      - &data
+        source: data
+        target: /var/lib
+      - &cache /tmp/cache
+  proxy:
+    image: *image
+    volumes: [*data, *cache]
+"#;
+
+#[test]
+fn test_compose_round_trip() {
+    let parsed = parse_with(COMPOSE, &preserve()).unwrap();
+    assert_eq!(emit(&parsed), COMPOSE.trim_end());
+
+    let logging = parsed.get("x-logging").unwrap();
+    assert_eq!(logging.anchor.as_deref(), Some("logging"));
+    let web = parsed.get_path("services.web").unwrap();
+    assert_eq!(
+        web.get("logging").and_then(YamlNode::as_alias),
+        Some("logging")
+    );
+    assert_eq!(
+        web.get("logging").unwrap().inline_comment.as_deref(),
+        Some("Shared")
+    );
+    assert!(parsed
+        .get_path("services.proxy.volumes.0")
+        .unwrap()
+        .is_alias());
+    assert_eq!(
+        parsed.get_path("services.proxy.volumes.0.source"),
+        None,
+        "aliases aren't expanded"
+    );
+}
+
+#[test]
+fn test_list_and_resolve_anchors() {
+    let parsed = parse_with(COMPOSE, &preserve()).unwrap();
+    let names: Vec<&str> = parsed.anchors().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["logging", "ports", "image", "data", "cache"]);
+
+    for (path, target) in [
+        ("services.proxy.image", "nginx"),
+        ("services.proxy.volumes.1", "/tmp/cache"),
+    ] {
+        let node = parsed.resolve_alias(path).unwrap();
+        assert_eq!(node.as_str(), Some(target));
+    }
+    assert_eq!(
+        parsed
+            .resolve_alias("services.proxy.volumes.0")
+            .and_then(|n| n.get("target"))
+            .and_then(YamlNode::as_str),
+        Some("/var/lib")
+    );
+    // Only aliases resolve
+    assert_eq!(parsed.resolve_alias("services.web.image"), None);
+    assert_eq!(parsed.resolve_alias("missing"), None);
+
+    // Aliases still need an anchor before them, outside of them
+    assert_eq!(
+        parse_with("a: *missing", &preserve()).unwrap_err(),
+        "Alias *missing has no anchor"
+    );
+    assert!(parse_with("a: &a\n  b: *a", &preserve()).is_err());
+}
+
+#[test]
+fn test_resolve_redefined_anchor() {
+    let parsed = parse_with("a: &x one\nb: *x\nc: &x two\nd: *x", &preserve()).unwrap();
+
+    // Each alias refers to the nearest anchor before it
+    assert_eq!(
+        parsed.resolve_alias("b").and_then(YamlNode::as_str),
+        Some("one")
+    );
+    assert_eq!(
+        parsed.resolve_alias("d").and_then(YamlNode::as_str),
+        Some("two")
+    );
+}
+
+#[test]
+fn test_emit_built_anchors() {
+    let tree = YamlNode::from(
+        YamlObject::new()
+            .with(
+                "defaults",
+                YamlNode::from(YamlObject::new().with_string("retries", "3")).with_anchor("base"),
+            )
+            .with("items", vec![YamlNode::alias("base"), "x".into()])
+            .with("root", YamlNode::alias("base")),
+    );
+    let output = emit_checked(&tree).unwrap();
+    assert_eq!(
+        output,
+        "defaults: &base\n  retries: \"3\"\nitems:\n  - *base\n  - x\nroot: *base"
+    );
+
+    // Short flow style keeps aliases and anchors
+    let options = EmitOptions::new().with_flow_style(FlowStyle::Short { max_width: 80 });
+    assert_eq!(
+        emit_with(&tree, &options),
+        "defaults: &base {retries: \"3\"}\nitems: [*base, x]\nroot: *base"
+    );
+
+    // An anchored root goes on its own line above a block collection
+    let root = parse_with("&top\n- a\n- &b b", &preserve()).unwrap();
+    assert_eq!(root.anchor.as_deref(), Some("top"));
+    assert_eq!(emit(&root), "&top\n- a\n- &b b");
+    assert!(matches!(&root.value, YamlValue::Array(items) if items.len() == 2));
+}
@@ -0,0 +1,100 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, parse};
+
+const CONFIG: &str = r#"server:
+  # Port the server listens on
+  # @type: port
+  port: 8080
+  # @deprecated: use server.listen
+  address: 0.0.0.0
+database:
+  # @secret
+  # @type: string
+  password: hunter2
+users:
+  # @secret
+  - admin
+"#;
+
+#[test]
+fn test_annotations() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    let port = parsed.get_path("server.port").unwrap();
+    assert_eq!(port.annotations(), vec![("type", "port")]);
+    // The comment text is left as written
+    assert_eq!(
+        port.leading_comment.as_deref(),
+        Some("Port the server listens on\n@type: port")
+    );
+
+    let password = parsed.get_path("database.password").unwrap();
+    assert_eq!(
+        password.annotations(),
+        vec![("secret", ""), ("type", "string")]
+    );
+}
+
+#[test]
+fn test_annotation_lookup() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    let address = parsed.get_path("server.address").unwrap();
+    assert_eq!(address.annotation("deprecated"), Some("use server.listen"));
+    assert_eq!(address.annotation("secret"), None);
+}
+
+#[test]
+fn test_not_annotations() {
+    let yaml = r#"# Contact admin@example.com for access
+# @ not a name
+# @todo(later)
+key: value
+"#;
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    assert!(parsed.get("key").unwrap().annotations().is_empty());
+}
+
+#[test]
+fn test_annotation_without_colon() {
+    let yaml = "# @since 1.2\nkey: value";
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    assert_eq!(
+        parsed.get("key").unwrap().annotations(),
+        vec![("since", "1.2")]
+    );
+}
+
+#[test]
+fn test_find_annotated() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    assert_eq!(
+        parsed.find_annotated("secret"),
+        vec![
+            ("database.password".to_string(), ""),
+            ("users.0".to_string(), "")
+        ]
+    );
+    assert_eq!(
+        parsed.find_annotated("type"),
+        vec![
+            ("server.port".to_string(), "port"),
+            ("database.password".to_string(), "string")
+        ]
+    );
+}
+
+#[test]
+fn test_annotations_survive_emit() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+    let reparsed = parse(&emit(&parsed)).expect("Failed to reparse");
+
+    assert_eq!(
+        reparsed.find_annotated("secret"),
+        parsed.find_annotated("secret")
+    );
+}
@@ -58,6 +58,31 @@ fn test_array_of_objects_inline_format() {
     }
 }
 
+#[test]
+fn test_array_in_array_item_object() {
+    let yaml = "hosts:\n  - name: web\n    roles:\n      - web\n      - cache\n  - name: db\n    roles:\n      - db\nlists:\n  - - a\n    - b\n  - c";
+    let result = parse(yaml).expect("Failed to parse YAML");
+
+    let hosts = result.get("hosts").and_then(|h| h.as_array()).unwrap();
+    assert_eq!(hosts.len(), 2);
+    assert_eq!(
+        hosts[0]
+            .get("roles")
+            .and_then(|r| r.as_array())
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(hosts[1].get("name").and_then(|n| n.as_str()), Some("db"));
+
+    let lists = result.get("lists").and_then(|l| l.as_array()).unwrap();
+    assert_eq!(lists.len(), 2);
+    assert_eq!(lists[0].as_array().unwrap().len(), 2);
+    assert_eq!(lists[1].as_str(), Some("c"));
+
+    assert_eq!(parse(&emit(&result)).expect("Failed to reparse"), result);
+}
+
 #[test]
 fn test_manual_array_construction() {
     let items = vec![
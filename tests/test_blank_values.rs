@@ -0,0 +1,64 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, emit_checked, parse, YamlNode, YamlObject, YamlValue};
+
+#[test]
+fn test_blank_is_not_empty_string_or_collection() {
+    let yaml = "blank:\nempty: \"\"\nlist: []\nmap: {}\nnull_text: null\ntilde: ~";
+    let parsed = parse(yaml).unwrap();
+    let get = |key: &str| parsed.get(key).unwrap();
+    assert!(get("blank").is_blank());
+    assert_eq!(get("empty").as_str(), Some(""));
+    assert_eq!(get("list").value, YamlValue::Array(Vec::new()));
+    assert!(get("map").as_object().is_some_and(|obj| obj.is_empty()));
+    // No implicit null typing
+    assert_eq!(get("null_text").as_str(), Some("null"));
+    assert_eq!(get("tilde").as_str(), Some("~"));
+    assert!(!get("empty").is_blank());
+}
+
+#[test]
+fn test_blank_items_and_properties() {
+    let yaml = "items:\n  -\n  - # note\n  - x\ntagged: !!null\nlast:";
+    let parsed = parse(yaml).unwrap();
+    let items = parsed.get("items").and_then(YamlNode::as_array).unwrap();
+    assert!(items[0].is_blank());
+    assert!(items[1].is_blank());
+    assert_eq!(items[1].inline_comment.as_deref(), Some("note"));
+    let tagged = parsed.get("tagged").unwrap();
+    assert!(tagged.is_blank());
+    assert_eq!(tagged.tag.as_deref(), Some("!!null"));
+    assert!(parsed.get("last").is_some_and(YamlNode::is_blank));
+    assert_eq!(emit_checked(&parsed).unwrap(), yaml);
+
+    let root = parse("-\n-").unwrap();
+    assert!(root
+        .as_array()
+        .is_some_and(|items| items.iter().all(YamlNode::is_blank)));
+}
+
+#[test]
+fn test_emit_empty_collections_and_blanks() {
+    let node = YamlNode::from(
+        YamlObject::new()
+            .with("list", YamlNode::from_value(YamlValue::Array(Vec::new())))
+            .with("map", YamlObject::new())
+            .with("blank", YamlNode::blank())
+            .with("empty", ""),
+    );
+    let output = emit(&node);
+    assert_eq!(output, "list: []\nmap: {}\nblank:\nempty: \"\"");
+    assert_eq!(emit(&parse(&output).unwrap()), output);
+
+    // Empty roots are written in flow style too
+    assert_eq!(emit(&YamlNode::from(YamlObject::new())), "{}");
+}
+
+#[test]
+fn test_blank_flow_entries() {
+    let parsed = parse("flags: {debug, level: }\nlist: [a, b]").unwrap();
+    let flags = parsed.get("flags").unwrap();
+    assert!(flags.get("debug").is_some_and(YamlNode::is_blank));
+    assert!(flags.get("level").is_some_and(YamlNode::is_blank));
+    assert_eq!(emit(&parsed), "flags: {debug, level}\nlist: [a, b]");
+}
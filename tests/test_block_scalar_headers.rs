@@ -0,0 +1,98 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, emit_checked, parse, YamlNode, YamlObject};
+
+fn text<'n>(root: &'n YamlNode, path: &str) -> Option<&'n str> {
+    root.get_path(path).and_then(YamlNode::as_str)
+}
+
+#[test]
+fn test_literal_keeps_relative_indentation() {
+    let yaml = r#"steps:
+  - name: Build
+    script: |
+      if [ -f Makefile ]; then
+          make all
+
+          make test
+      fi
+  - |
+    def main():
+        run()
+"#;
+    let parsed = parse(yaml).unwrap();
+    assert_eq!(
+        text(&parsed, "steps.0.script"),
+        Some("if [ -f Makefile ]; then\n    make all\n\n    make test\nfi\n")
+    );
+    assert_eq!(text(&parsed, "steps.1"), Some("def main():\n    run()\n"));
+    assert_eq!(emit_checked(&parsed).unwrap(), yaml.trim_end());
+}
+
+#[test]
+fn test_indentation_indicators() {
+    let yaml = "code: |2\n    indented\n  first\nfolded: >-4\n      deeper\n     less\nkept: |+1\n  x\n\nlast: x\nitems:\n  - |1-\n     y\n";
+    let parsed = parse(yaml).unwrap();
+    assert_eq!(text(&parsed, "code"), Some("  indented\nfirst\n"));
+    assert_eq!(text(&parsed, "folded"), Some("  deeper\n less"));
+    assert_eq!(text(&parsed, "kept"), Some(" x\n\n"));
+    assert_eq!(text(&parsed, "last"), Some("x"));
+    assert_eq!(text(&parsed, "items.0"), Some("  y"));
+
+    // The indicators can come in either order
+    let parsed = parse("a: |-2\n    x\nb: |2-\n    x").unwrap();
+    assert_eq!(text(&parsed, "a"), text(&parsed, "b"));
+}
+
+#[test]
+fn test_folded_block_lines() {
+    let yaml =
+        "text: >\n  Folded\n  lines\n\n  after a blank\n    more indented\n    kept\n  back\n";
+    let parsed = parse(yaml).unwrap();
+    assert_eq!(
+        text(&parsed, "text"),
+        Some("Folded lines\nafter a blank\n  more indented\n  kept\nback\n")
+    );
+}
+
+#[test]
+fn test_emits_indentation_indicator() {
+    let node = YamlNode::from(
+        YamlObject::new()
+            .with("code", "    return 1\nend\n")
+            .with("items", vec![YamlNode::from(" a\nb")]),
+    );
+    let output = emit(&node);
+    assert_eq!(
+        output,
+        "code: |2\n      return 1\n  end\nitems:\n  - |2-\n     a\n    b"
+    );
+    assert_eq!(parse(&output).unwrap(), node);
+}
+
+#[test]
+fn test_single_lines_and_kept_newlines_round_trip() {
+    for yaml in [
+        "a: |1\n  lead\n",
+        "a: |\n  x\n",
+        "- |\n  x\n",
+        "a: |+\n  x\n\n",
+        "a: |+\n  x\n\n\nb: c",
+        "- |+\n  x\n\n- |2+\n   y\n\n",
+        "a: |+\n\n",
+    ] {
+        let parsed = parse(yaml).unwrap();
+        let output = emit_checked(&parsed).unwrap();
+        assert_eq!(parse(&output).unwrap(), parsed, "{:?}", yaml);
+    }
+
+    let node = YamlNode::from(
+        YamlObject::new()
+            .with("one", "x\n")
+            .with("kept", "x\n\n")
+            .with("last", "y\n\n\n"),
+    );
+    let output = emit(&node);
+    assert_eq!(output, "one: |\n  x\nkept: |+\n  x\n\nlast: |+\n  y\n\n\n");
+    assert_eq!(parse(&output).unwrap(), node);
+}
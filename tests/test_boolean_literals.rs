@@ -36,10 +36,12 @@ fn test_boolean_like_values_parse_as_strings() {
                     value
                 );
             }
-            YamlValue::Object(_) | YamlValue::Array(_) => panic!(
-                "Expected '{}' to be parsed as a string, got: {:?}",
-                value, parsed.value
-            ),
+            YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+                panic!(
+                    "Expected '{}' to be parsed as a string, got: {:?}",
+                    value, parsed.value
+                )
+            }
         }
     }
 }
@@ -131,10 +133,12 @@ fn test_case_sensitive_strings() {
             YamlValue::String(s) => {
                 assert_eq!(s.as_str(), value);
             }
-            YamlValue::Object(_) | YamlValue::Array(_) => panic!(
-                "Expected '{}' to be parsed as a string, got: {:?}",
-                value, parsed.value
-            ),
+            YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+                panic!(
+                    "Expected '{}' to be parsed as a string, got: {:?}",
+                    value, parsed.value
+                )
+            }
         }
     }
 }
@@ -0,0 +1,94 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit_with, parse, parse_with, AliasMode, CollectionStyle, EmitOptions, LineEnding,
+    ParseOptions, YamlNode, YamlObject,
+};
+
+#[test]
+fn test_canonical_sorts_keys_with_comments() {
+    let yaml = r#"# Service name
+name: api
+server:
+  port: "8080" # Listen port
+  # Bind address
+  host: 0.0.0.0
+build:
+  - cargo build
+  - cargo test
+"#;
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    let expected = r#"build:
+  - cargo build
+  - cargo test
+# Service name
+name: api
+server:
+  # Bind address
+  host: 0.0.0.0
+  port: 8080 # Listen port"#;
+    assert_eq!(emit_with(&parsed, &EmitOptions::canonical()), expected);
+}
+
+#[test]
+fn test_canonical_ignores_how_tree_was_built() {
+    let parsed = parse("b:\n  - \"1\"\n  - '2'\na:\n  y: x\n  x: y").expect("Failed to parse");
+
+    let built: YamlNode = YamlObject::new()
+        .with(
+            "a",
+            YamlNode::from(YamlObject::new().with("x", "y").with("y", "x"))
+                .with_style(CollectionStyle::Flow),
+        )
+        .with(
+            "b",
+            YamlNode::from(vec![1, 2]).with_style(CollectionStyle::Block),
+        )
+        .into();
+
+    let options = EmitOptions::canonical();
+    assert_eq!(emit_with(&parsed, &options), emit_with(&built, &options));
+}
+
+#[test]
+fn test_canonical_layout_is_fixed() {
+    let parsed = parse("z: 1\na:\n  - x").expect("Failed to parse");
+
+    // Layout settings are overridden; canonical output has one form
+    let options = EmitOptions::canonical()
+        .with_indent(4)
+        .with_line_ending(LineEnding::CrLf);
+    assert_eq!(emit_with(&parsed, &options), "a:\n  - x\nz: 1");
+}
+
+#[test]
+fn test_canonical_without_comments() {
+    let with_comments = parse("# Port\nport: 80 # HTTP\n# End").expect("Failed to parse");
+    let without = parse("port: 80").expect("Failed to parse");
+
+    let options = EmitOptions::canonical().with_comments(false);
+    assert_eq!(
+        emit_with(&with_comments, &options),
+        emit_with(&without, &options)
+    );
+}
+
+#[test]
+fn test_canonical_keeps_anchors_before_aliases() {
+    let options = ParseOptions::new().with_aliases(AliasMode::Preserve);
+    let yaml = "z: &x v\na: *x\nother:\n  b: 1\n  a: 2\nnested:\n  z:\n    k: &y w\n  a: [*y]";
+    let parsed = parse_with(yaml, &options).expect("Failed to parse");
+
+    // Mappings whose sorting would put an alias first keep their order
+    let output = emit_with(&parsed, &EmitOptions::canonical());
+    assert_eq!(
+        output,
+        "z: &x v\na: *x\nother:\n  a: 2\n  b: 1\nnested:\n  z:\n    k: &y w\n  a:\n    - *y"
+    );
+    let reparsed = parse_with(&output, &options).expect("Failed to reparse");
+    assert_eq!(
+        reparsed.resolve_alias("a").and_then(YamlNode::as_str),
+        Some("v")
+    );
+}
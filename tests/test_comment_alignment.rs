@@ -0,0 +1,93 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, emit_with, parse, EmitOptions};
+
+const CONFIG: &str = r#"server:
+  host: localhost # Bind address
+  port: "8080" # HTTP port
+  tls: # TLS settings
+    enabled: "true"
+  timeout_seconds: "30"
+tags: # Labels
+  - web # Public
+  - internal-service # Private
+name: api # Service name"#;
+
+#[test]
+fn test_align_comments_per_block() {
+    let parsed = parse(CONFIG).unwrap();
+    let options = EmitOptions::new().with_align_comments(true);
+    let output = emit_with(&parsed, &options);
+    assert_eq!(
+        output,
+        r#"server:
+  host: localhost # Bind address
+  port: "8080"    # HTTP port
+  tls:            # TLS settings
+    enabled: "true"
+  timeout_seconds: "30"
+tags:     # Labels
+  - web              # Public
+  - internal-service # Private
+name: api # Service name"#
+    );
+    assert_eq!(parse(&output).unwrap(), parsed);
+
+    // Off by default
+    assert_eq!(emit(&parsed), CONFIG);
+}
+
+#[test]
+fn test_align_values() {
+    let parsed = parse(CONFIG).unwrap();
+    let options = EmitOptions::new()
+        .with_align_values(true)
+        .with_align_comments(true);
+    let output = emit_with(&parsed, &options);
+    assert_eq!(
+        output,
+        r#"server:
+  host:            localhost # Bind address
+  port:            "8080"    # HTTP port
+  tls:                       # TLS settings
+    enabled: "true"
+  timeout_seconds: "30"
+tags:     # Labels
+  - web              # Public
+  - internal-service # Private
+name: api # Service name"#
+    );
+    assert_eq!(parse(&output).unwrap(), parsed);
+}
+
+#[test]
+fn test_align_in_sequence_of_mappings() {
+    let parsed = parse("- name: a # First\n  image: nginx # Pinned later").unwrap();
+    let options = EmitOptions::new()
+        .with_align_values(true)
+        .with_align_comments(true);
+    assert_eq!(
+        emit_with(&parsed, &options),
+        "- name:  a     # First\n  image: nginx # Pinned later"
+    );
+}
+
+#[test]
+fn test_wrap_leading_comments() {
+    let parsed = parse(
+        "# The address the service listens on, as a host name or an IP address\n# @default: localhost and nothing else at all\nhost: localhost\nnested:\n  # Connection timeout applied to every upstream request\n  timeout: \"30\"",
+    )
+    .unwrap();
+    let options = EmitOptions::new().with_comment_width(40);
+    assert_eq!(
+        emit_with(&parsed, &options),
+        r#"# The address the service listens on, as
+# a host name or an IP address
+# @default: localhost and nothing else at all
+host: localhost
+nested:
+  # Connection timeout applied to every
+  # upstream request
+  timeout: "30""#
+    );
+}
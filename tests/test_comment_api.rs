@@ -0,0 +1,224 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, emit_with, parse, CommentSlot, EmitOptions, ScalarStyle, YamlNode, YamlObject};
+
+const CONFIG: &str = r#"# Server settings
+server:
+  host: localhost # Bind address
+  port: 8080
+features:
+  # Always on
+  - logging
+  - metrics # Experimental
+"#;
+
+#[test]
+fn test_get_path() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    assert_eq!(
+        parsed.get_path("server.host").and_then(|n| n.as_str()),
+        Some("localhost")
+    );
+    assert_eq!(
+        parsed.get_path("features.1").and_then(|n| n.as_str()),
+        Some("metrics")
+    );
+    assert_eq!(parsed.get_path(""), Some(&parsed));
+    assert!(parsed.get_path("server.missing").is_none());
+    assert!(parsed.get_path("features.7").is_none());
+    assert!(parsed.get_path("server.host.deeper").is_none());
+}
+
+#[test]
+fn test_quoted_path_segments() {
+    let yaml = "metadata:\n  labels:\n    app.kubernetes.io/name: web\n    tier: front\n";
+    let mut parsed = parse(yaml).expect("Failed to parse");
+    let label = r#"metadata.labels."app.kubernetes.io/name""#;
+
+    assert_eq!(parsed.get_path(label).and_then(|n| n.as_str()), Some("web"));
+    assert!(parsed
+        .get_path("metadata.labels.app.kubernetes.io/name")
+        .is_none());
+
+    // Reported paths quote such keys, so they can be used again
+    parsed
+        .set_comment(label, CommentSlot::Inline, "Set by the chart")
+        .unwrap();
+    let found: Vec<_> = parsed.comments().map(|entry| entry.path).collect();
+    assert_eq!(found, vec![label.to_string()]);
+
+    let options = EmitOptions::new().with_style_at(label, ScalarStyle::Quoted);
+    assert!(emit_with(&parsed, &options).contains("app.kubernetes.io/name: \"web\""));
+
+    parsed.disable(label).expect("Failed to disable");
+    let mut reparsed = parse(&emit(&parsed)).expect("Failed to reparse");
+    assert_eq!(reparsed.commented_entries()[0].path, label);
+    reparsed.enable(label).expect("Failed to enable");
+    assert_eq!(
+        reparsed.get_path(label).and_then(|n| n.as_str()),
+        Some("web")
+    );
+
+    // Quotes and backslashes inside a quoted segment are escaped
+    let node = YamlNode::from(YamlObject::new().with("say \"hi\" \\o/", "x"));
+    assert_eq!(
+        node.get_path(r#""say \"hi\" \\o/""#)
+            .and_then(|n| n.as_str()),
+        Some("x")
+    );
+}
+
+#[test]
+fn test_set_comment() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed
+        .set_comment("server", CommentSlot::Leading, "Managed by X, do not edit")
+        .expect("Failed to set comment");
+    parsed
+        .set_comment("server.port", CommentSlot::Inline, "Listen port")
+        .expect("Failed to set comment");
+
+    let server = parsed.get("server").unwrap();
+    assert_eq!(
+        server.leading_comment.as_deref(),
+        Some("Managed by X, do not edit")
+    );
+    assert_eq!(
+        server.get("port").unwrap().inline_comment.as_deref(),
+        Some("Listen port")
+    );
+
+    let output = emit(&parsed);
+    assert!(output.contains("# Managed by X, do not edit\nserver:"));
+    let port_line = output
+        .lines()
+        .find(|line| line.trim_start().starts_with("port:"))
+        .expect("port line not emitted");
+    assert!(port_line.ends_with(" # Listen port"));
+}
+
+#[test]
+fn test_set_comment_missing_path() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    let result = parsed.set_comment("server.missing", CommentSlot::Leading, "note");
+    assert_eq!(result, Err("Path not found: server.missing".to_string()));
+}
+
+#[test]
+fn test_inline_comment_must_be_one_line() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    let error = Err("Inline comments must be a single line: server.host".to_string());
+    assert_eq!(
+        parsed.set_comment("server.host", CommentSlot::Inline, "one\ntwo"),
+        error
+    );
+    assert_eq!(
+        parsed.append_comment("server.host", CommentSlot::Inline, "one\ntwo"),
+        error
+    );
+    assert_eq!(
+        parsed
+            .get_path("server.host")
+            .unwrap()
+            .inline_comment
+            .as_deref(),
+        Some("Bind address")
+    );
+
+    // At the root the slot holds trailing comment lines
+    parsed
+        .set_comment("", CommentSlot::Inline, "one\ntwo")
+        .unwrap();
+    assert!(emit(&parsed).ends_with("\n# one\n# two\n"));
+}
+
+#[test]
+fn test_append_comment() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed
+        .append_comment("server", CommentSlot::Leading, "Managed by X")
+        .unwrap();
+    parsed
+        .append_comment("server.host", CommentSlot::Inline, "(override in prod)")
+        .unwrap();
+    parsed
+        .append_comment("server.port", CommentSlot::Leading, "Listen port")
+        .unwrap();
+    parsed
+        .append_comment("", CommentSlot::Inline, "End of file")
+        .unwrap();
+
+    let server = parsed.get("server").unwrap();
+    assert_eq!(
+        server.leading_comment.as_deref(),
+        Some("Server settings\nManaged by X")
+    );
+    assert_eq!(
+        server.get("host").unwrap().inline_comment.as_deref(),
+        Some("Bind address (override in prod)")
+    );
+    assert_eq!(
+        server.get("port").unwrap().leading_comment.as_deref(),
+        Some("Listen port")
+    );
+    assert_eq!(parsed.inline_comment.as_deref(), Some("End of file"));
+}
+
+#[test]
+fn test_remove_comments() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed.remove_comments("server", false).unwrap();
+    let server = parsed.get("server").unwrap();
+    assert_eq!(server.leading_comment, None);
+    // Children keep their comments when not recursive
+    assert_eq!(
+        server.get("host").unwrap().inline_comment.as_deref(),
+        Some("Bind address")
+    );
+
+    parsed.remove_comments("", true).unwrap();
+    assert_eq!(parsed.comments().count(), 0);
+    assert!(!emit(&parsed).contains('#'));
+}
+
+#[test]
+fn test_comments_iterator() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    let found: Vec<_> = parsed
+        .comments()
+        .map(|entry| (entry.path, entry.slot, entry.text.to_string()))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (
+                "server".to_string(),
+                CommentSlot::Leading,
+                "Server settings".to_string()
+            ),
+            (
+                "server.host".to_string(),
+                CommentSlot::Inline,
+                "Bind address".to_string()
+            ),
+            (
+                "features.0".to_string(),
+                CommentSlot::Leading,
+                "Always on".to_string()
+            ),
+            (
+                "features.1".to_string(),
+                CommentSlot::Inline,
+                "Experimental".to_string()
+            ),
+        ]
+    );
+}
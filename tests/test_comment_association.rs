@@ -521,6 +521,8 @@ fn test_empty_file_with_only_comments() {
             YamlValue::Object(map) => assert_eq!(map.len(), 0),
             YamlValue::String(s) => assert!(s.is_empty() || s.starts_with('#')),
             YamlValue::Array(arr) => assert_eq!(arr.len(), 0),
+            YamlValue::Alias(name) => panic!("Unexpected alias *{}", name),
+            YamlValue::Blank => {}
         }
     }
     // It's also acceptable to return an error for a file with no content
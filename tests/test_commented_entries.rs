@@ -0,0 +1,311 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, parse, parse_with, AliasMode, CommentedEntry, ParseOptions};
+
+const CONFIG: &str = r#"# Application name
+name: MyApp
+# Verbose logging
+# log_level: debug
+port: 8080
+features:
+  - logging
+  # - experimental
+  - metrics
+# timeout: 30
+"#;
+
+fn entry(path: &str, text: &str) -> CommentedEntry {
+    CommentedEntry {
+        path: path.to_string(),
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn test_find_commented_entries() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    assert_eq!(
+        parsed.commented_entries(),
+        vec![
+            entry("log_level", "log_level: debug"),
+            entry("features.1", "- experimental"),
+            entry("timeout", "timeout: 30"),
+        ]
+    );
+}
+
+#[test]
+fn test_prose_is_not_an_entry() {
+    let yaml = r#"# Default settings: see the docs
+# Set this before deploying
+key: value
+items:
+  # Keep sorted
+  - a
+"#;
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    assert!(parsed.commented_entries().is_empty());
+}
+
+#[test]
+fn test_prose_with_a_colon_is_not_an_entry() {
+    let yaml = r#"# TODO: fix this later
+# Note: see docs
+# Example:
+log_level: info
+items:
+  # - see the docs
+  - a
+"#;
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    assert!(parsed.commented_entries().is_empty());
+
+    // Quoted words are still a value
+    let parsed = parse("# name: \"My app\"\nport: 8080").expect("Failed to parse");
+    assert_eq!(
+        parsed.commented_entries(),
+        vec![entry("name", "name: \"My app\"")]
+    );
+}
+
+#[test]
+fn test_enable_mapping_entry() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed.enable("log_level").expect("Failed to enable");
+
+    let log_level = parsed.get("log_level").expect("log_level not enabled");
+    assert_eq!(log_level.as_str(), Some("debug"));
+    // The description above the entry moves with it
+    assert_eq!(
+        log_level.leading_comment.as_deref(),
+        Some("Verbose logging")
+    );
+    assert_eq!(parsed.get("port").unwrap().leading_comment, None);
+
+    let keys: Vec<_> = parsed.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(keys, vec!["name", "log_level", "port", "features"]);
+}
+
+#[test]
+fn test_enable_sequence_item() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed.enable("features.1").expect("Failed to enable");
+
+    let features: Vec<_> = parsed
+        .get("features")
+        .and_then(|n| n.as_array())
+        .unwrap()
+        .iter()
+        .filter_map(|n| n.as_str())
+        .collect();
+    assert_eq!(features, vec!["logging", "experimental", "metrics"]);
+}
+
+#[test]
+fn test_enable_trailing_entry() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed.enable("timeout").expect("Failed to enable");
+
+    assert_eq!(parsed.get("timeout").and_then(|n| n.as_str()), Some("30"));
+    assert_eq!(parsed.inline_comment, None);
+    assert!(parsed
+        .commented_entries()
+        .iter()
+        .all(|e| e.path != "timeout"));
+}
+
+#[test]
+fn test_disable_and_enable_round_trip() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+    let original = parsed.clone();
+
+    parsed.disable("name").expect("Failed to disable");
+    assert!(parsed.get("name").is_none());
+    assert_eq!(
+        parsed.get("log_level").map(|_| ()),
+        None,
+        "neighbours must not change"
+    );
+
+    let output = emit(&parsed);
+    assert!(output.starts_with("# Application name\n# name: MyApp\n# Verbose logging"));
+
+    // The disabled entry survives a parse/emit cycle as a comment
+    let mut reparsed = parse(&output).expect("Failed to reparse");
+    reparsed.enable("name").expect("Failed to enable");
+    assert_eq!(reparsed, original);
+}
+
+#[test]
+fn test_disable_sequence_item() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    parsed.disable("features.0").expect("Failed to disable");
+
+    let features = parsed.get("features").and_then(|n| n.as_array()).unwrap();
+    assert_eq!(features.len(), 1);
+    assert_eq!(features[0].as_str(), Some("metrics"));
+    assert_eq!(
+        features[0].leading_comment.as_deref(),
+        Some("- logging\n- experimental")
+    );
+}
+
+#[test]
+fn test_disable_errors() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+
+    assert_eq!(
+        parsed.disable("missing"),
+        Err("Path not found: missing".to_string())
+    );
+    assert_eq!(
+        parsed.disable("features"),
+        Err("Only single-line entries can be disabled: features".to_string())
+    );
+    let mut single = parse("outer:\n  only: 1\nnext: 2").expect("Failed to parse");
+    assert_eq!(
+        single.disable("outer.only"),
+        Err("Cannot disable the only entry of a nested block: outer.only".to_string())
+    );
+    assert_eq!(
+        parsed.enable("name"),
+        Err("No commented-out entry at: name".to_string())
+    );
+}
+
+#[test]
+fn test_entries_follow_comment_indentation() {
+    // After the last entry of a block, at that block's indentation
+    let parsed = parse("server:\n  host: x\n  # port: 80\nname: y").expect("Failed to parse");
+    assert_eq!(
+        parsed.commented_entries(),
+        vec![entry("server.port", "port: 80")]
+    );
+
+    // Inside a block, at the indentation of the enclosing one
+    let parsed = parse("server:\n  host: x\n# name: z\n  port: 80").expect("Failed to parse");
+    assert_eq!(parsed.commented_entries(), vec![entry("name", "name: z")]);
+
+    // At the end of the document, inside the last block
+    let parsed = parse("name: y\nserver:\n  host: x\n  # port: 80\n").expect("Failed to parse");
+    assert_eq!(
+        parsed.commented_entries(),
+        vec![entry("server.port", "port: 80")]
+    );
+}
+
+#[test]
+fn test_enable_entry_at_end_of_block() {
+    let mut parsed = parse("server:\n  host: x\n  # port: 80\nname: y").expect("Failed to parse");
+
+    parsed.enable("server.port").expect("Failed to enable");
+
+    assert_eq!(
+        parsed.get_path("server.port").and_then(|n| n.as_str()),
+        Some("80")
+    );
+    assert!(parsed.get("port").is_none());
+    assert_eq!(parsed.get("name").unwrap().leading_comment, None);
+}
+
+#[test]
+fn test_enable_entry_of_outer_block() {
+    let mut parsed = parse("server:\n  host: x\n# name: z\n  port: 80").expect("Failed to parse");
+
+    parsed.enable("name").expect("Failed to enable");
+
+    let keys: Vec<_> = parsed.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(keys, vec!["server", "name"]);
+    assert!(parsed.get_path("server.name").is_none());
+    assert_eq!(
+        parsed.get_path("server.port").and_then(|n| n.as_str()),
+        Some("80")
+    );
+}
+
+#[test]
+fn test_disable_last_entry_of_nested_block() {
+    let mut parsed = parse(CONFIG).expect("Failed to parse");
+    let original = parsed.clone();
+
+    parsed.disable("features.1").expect("Failed to disable");
+    let output = emit(&parsed);
+    assert!(output.contains("  - logging\n  # - experimental\n  # - metrics\n# timeout: 30"));
+
+    let mut reparsed = parse(&output).expect("Failed to reparse");
+    assert_eq!(
+        reparsed.commented_entries(),
+        vec![
+            entry("log_level", "log_level: debug"),
+            entry("features.1", "- experimental"),
+            entry("features.2", "- metrics"),
+            entry("timeout", "timeout: 30"),
+        ]
+    );
+    reparsed.enable("features.2").expect("Failed to enable");
+    assert_eq!(reparsed, original);
+}
+
+#[test]
+fn test_disable_last_entry_of_document() {
+    let mut parsed = parse("name: y\nserver:\n  host: x\n  port: 80").expect("Failed to parse");
+
+    parsed.disable("server.port").expect("Failed to disable");
+
+    let output = emit(&parsed);
+    assert!(output.contains("  host: x\n  # port: "));
+    let mut reparsed = parse(&output).expect("Failed to reparse");
+    reparsed.enable("server.port").expect("Failed to enable");
+    assert_eq!(
+        reparsed.get_path("server.port").and_then(|n| n.as_str()),
+        Some("80")
+    );
+}
+
+#[test]
+fn test_disable_blank_and_flow_entries() {
+    let mut parsed =
+        parse("key:\nos: [linux, macos]\nname: My app\nport: 8080").expect("Failed to parse");
+    let original = parsed.clone();
+
+    for path in ["key", "os", "name"] {
+        parsed.disable(path).expect("Failed to disable");
+    }
+    let output = emit(&parsed);
+    assert!(output.starts_with("# key:\n# os: [linux, macos]\n# name: \"My app\"\nport:"));
+
+    let mut reparsed = parse(&output).expect("Failed to reparse");
+    for path in ["key", "os", "name"] {
+        reparsed.enable(path).expect("Failed to enable");
+    }
+    assert_eq!(emit(&reparsed), emit(&original));
+}
+
+#[test]
+fn test_disable_anchored_entry() {
+    let options = ParseOptions::new().with_aliases(AliasMode::Preserve);
+
+    // An alias still refers to the anchor
+    let mut parsed = parse_with("a: &x 1\nb: 2\nc: *x", &options).expect("Failed to parse");
+    assert_eq!(
+        parsed.disable("a"),
+        Err("Cannot disable an entry whose anchor is in use: a".to_string())
+    );
+
+    // Otherwise the anchor is kept in the comment line
+    let mut parsed = parse_with("a: &x 1\nb: 2", &options).expect("Failed to parse");
+    parsed.disable("a").expect("Failed to disable");
+    let output = emit(&parsed);
+    assert!(output.starts_with("# a: &x"));
+
+    let mut reparsed = parse_with(&output, &options).expect("Failed to reparse");
+    reparsed.enable("a").expect("Failed to enable");
+    assert_eq!(reparsed.get("a").unwrap().anchor.as_deref(), Some("x"));
+}
@@ -36,3 +36,21 @@ fn test_comments_preserved_in_emit() {
     assert!(emitted.contains("John"));
     assert!(emitted.contains("30"));
 }
+
+#[test]
+fn test_inline_comment_before_nested_block() {
+    let yaml = "server: # Listener\n  host: localhost\nports: # Exposed\n  - \"80\"";
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    let server = parsed.get("server").expect("server not found");
+    assert_eq!(server.inline_comment.as_deref(), Some("Listener"));
+    assert_eq!(
+        server.get("host").and_then(|n| n.as_str()),
+        Some("localhost")
+    );
+    let ports = parsed.get("ports").expect("ports not found");
+    assert_eq!(ports.inline_comment.as_deref(), Some("Exposed"));
+    assert_eq!(ports.as_array().map(|items| items.len()), Some(1));
+
+    assert_eq!(emit(&parsed), yaml);
+}
@@ -0,0 +1,100 @@
+#![deny(clippy::all)]
+
+use yamp::{emit_documents, parse, parse_document, parse_documents, Directive, Document};
+
+const FIXTURE: &str = r#"%YAML 1.2
+%TAG !e! tag:example.com,2000:app/
+---
+invoice: !e!invoice
+  id: "34843"
+  total: !!str due
+  note: !local plain
+...
+%YAML 1.2
+---
+second: document"#;
+
+#[test]
+fn test_directives_are_kept() {
+    let documents = parse_documents(FIXTURE).unwrap();
+    assert_eq!(documents.len(), 2);
+    assert_eq!(
+        documents[0].directives,
+        vec![
+            Directive::Yaml("1.2".to_string()),
+            Directive::Tag {
+                handle: "!e!".to_string(),
+                prefix: "tag:example.com,2000:app/".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        documents[1].directives,
+        vec![Directive::Yaml("1.2".to_string())]
+    );
+    assert_eq!(emit_documents(&documents), FIXTURE);
+
+    // A single document with directives parses like any other
+    let root = parse("%YAML 1.1\n---\nkey: value").unwrap();
+    assert_eq!(root.get("key").and_then(|key| key.as_str()), Some("value"));
+}
+
+#[test]
+fn test_expand_tags() {
+    let documents = parse_documents(FIXTURE).unwrap();
+    let document = &documents[0];
+    let expand = |tag: &str| document.expand_tag(tag);
+    let tag = document.root.get("invoice").and_then(|n| n.tag.as_deref());
+    assert_eq!(tag, Some("!e!invoice"));
+    assert_eq!(
+        expand("!e!invoice").as_deref(),
+        Some("tag:example.com,2000:app/invoice")
+    );
+    assert_eq!(expand("!!str").as_deref(), Some("tag:yaml.org,2002:str"));
+    assert_eq!(expand("!local").as_deref(), Some("!local"));
+    assert_eq!(
+        expand("!<tag:example.com,2000:x>").as_deref(),
+        Some("tag:example.com,2000:x")
+    );
+
+    // Handles are scoped to their document
+    assert_eq!(documents[1].expand_tag("!e!invoice"), None);
+}
+
+#[test]
+fn test_emit_built_directives() {
+    let mut first = Document::new(parse("a: b").unwrap());
+    first
+        .directives
+        .push(Directive::Reserved("FOO bar".to_string()));
+    let mut second = Document::new(parse("c: d").unwrap());
+    second.directives.push(Directive::Yaml("1.2".to_string()));
+
+    // Directives get a `---` after them, and a `...` before them when a
+    // document precedes them
+    let output = emit_documents(&[first, second]);
+    assert_eq!(output, "%FOO bar\n---\na: b\n...\n%YAML 1.2\n---\nc: d");
+    assert_eq!(parse_documents(&output).unwrap().len(), 2);
+}
+
+#[test]
+fn test_directive_errors() {
+    for (yaml, error) in [
+        (
+            "%YAML 1.2\nkey: value",
+            "Directives must be followed by a --- marker",
+        ),
+        ("%YAML 1.2", "Directives must be followed by a --- marker"),
+        (
+            "%YAML 1.2\n%YAML 1.1\n---\na",
+            "Repeated directive: %YAML 1.1",
+        ),
+        (
+            "%TAG e tag:x\n---\na",
+            "Expected a handle and prefix in \"%TAG e tag:x\"",
+        ),
+        ("%YAML\n---\na", "Expected a version in \"%YAML\""),
+    ] {
+        assert_eq!(parse_document(yaml).unwrap_err(), error, "{}", yaml);
+    }
+}
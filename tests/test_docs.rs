@@ -0,0 +1,155 @@
+#![deny(clippy::all)]
+
+use yamp::docs::{self, DocRow};
+use yamp::parse;
+
+const TEMPLATE: &str = r#"# Application name
+name: MyApp
+version: 1.0.0 # Semantic version
+
+# Server settings
+server:
+  # Bind address
+  host: 0.0.0.0
+  port: 8080 # Listen port
+
+# Log destinations
+outputs:
+  - console
+  - file
+
+# Feature flags
+features:
+  - name: dashboard # Feature identifier
+    enabled: false
+  - name: beta_api
+    # Percentage of users
+    rollout: 25
+"#;
+
+fn row(path: &str, default: &str, description: &str) -> DocRow {
+    DocRow {
+        path: path.to_string(),
+        default: Some(default.to_string()),
+        description: description.to_string(),
+    }
+}
+
+#[test]
+fn test_sections() {
+    let template = parse(TEMPLATE).expect("Failed to parse");
+    let sections = docs::sections(&template);
+
+    let paths: Vec<_> = sections.iter().map(|s| s.path.as_str()).collect();
+    assert_eq!(paths, vec!["", "server", "features[]"]);
+
+    assert_eq!(
+        sections[0].rows,
+        vec![
+            row("name", "MyApp", "Application name"),
+            row("version", "1.0.0", "Semantic version"),
+            row("outputs", "console, file", "Log destinations"),
+        ]
+    );
+    assert_eq!(sections[1].description, "Server settings");
+    assert_eq!(
+        sections[1].rows,
+        vec![
+            row("server.host", "0.0.0.0", "Bind address"),
+            row("server.port", "8080", "Listen port"),
+        ]
+    );
+}
+
+#[test]
+fn test_array_of_objects_merges_fields() {
+    let template = parse(TEMPLATE).expect("Failed to parse");
+    let sections = docs::sections(&template);
+
+    let features = &sections[2];
+    assert_eq!(features.description, "Feature flags");
+    // Fields from every item are listed once, with the first default seen
+    assert_eq!(
+        features.rows,
+        vec![
+            row("features[].name", "dashboard", "Feature identifier"),
+            row("features[].enabled", "false", ""),
+            row("features[].rollout", "25", "Percentage of users"),
+        ]
+    );
+}
+
+#[test]
+fn test_markdown() {
+    let template = parse(TEMPLATE).expect("Failed to parse");
+
+    let expected = r#"| Key | Default | Description |
+| --- | --- | --- |
+| `name` | `MyApp` | Application name |
+| `version` | `1.0.0` | Semantic version |
+| `outputs` | `console, file` | Log destinations |
+
+## `server`
+
+Server settings
+
+| Key | Default | Description |
+| --- | --- | --- |
+| `server.host` | `0.0.0.0` | Bind address |
+| `server.port` | `8080` | Listen port |
+
+## `features[]`
+
+Feature flags
+
+| Key | Default | Description |
+| --- | --- | --- |
+| `features[].name` | `dashboard` | Feature identifier |
+| `features[].enabled` | `false` |  |
+| `features[].rollout` | `25` | Percentage of users |
+"#;
+    assert_eq!(docs::markdown(&template), expected);
+}
+
+#[test]
+fn test_markdown_escaping() {
+    let template = parse("# Either a | b\nmode: \"\"\nscript: |\n  echo a\n  echo b\n")
+        .expect("Failed to parse");
+
+    let output = docs::markdown(&template);
+    assert!(output.contains("| `mode` | `\"\"` | Either a \\| b |"));
+    assert!(output.contains("| `script` | `echo a\\necho b\\n` |  |"));
+}
+
+#[test]
+fn test_plain_text() {
+    let template =
+        parse("# Server settings\nserver:\n  port: 8080 # Listen port\n").expect("Failed to parse");
+
+    assert_eq!(
+        docs::plain_text(&template),
+        "[server]\nServer settings\n\nserver.port = 8080\n    Listen port\n"
+    );
+}
+
+#[test]
+fn test_annotations_are_not_descriptions() {
+    let template =
+        parse("# Listen port\n# @type: port\nport: 8080 # Default for dev\n# @secret\ntoken: x")
+            .unwrap();
+
+    let sections = docs::sections(&template);
+    assert_eq!(
+        sections[0].rows,
+        vec![
+            row("port", "8080", "Listen port Default for dev"),
+            row("token", "x", ""),
+        ]
+    );
+    assert!(docs::markdown(&template).contains("| `port` | `8080` | Listen port Default for dev |"));
+    assert!(!docs::plain_text(&template).contains('@'));
+    assert_eq!(
+        template.get("port").unwrap().annotation("type"),
+        Some("port")
+    );
+}
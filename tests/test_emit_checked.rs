@@ -0,0 +1,59 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, emit_checked, emit_checked_with, parse, EmitOptions, YamlNode, YamlValue};
+
+fn string(value: &str) -> YamlNode {
+    YamlNode::from_value(YamlValue::String(value.to_string()))
+}
+
+const CONFIG: &str = r#"# Service
+name: api # Public name
+server:
+  ports:
+    - "80"
+    - "443"
+script: |
+  make
+  make test
+"#;
+
+#[test]
+fn test_faithful_output_passes() {
+    let parsed = parse(CONFIG).unwrap();
+    assert_eq!(emit_checked(&parsed).unwrap(), emit(&parsed));
+
+    let options = EmitOptions::canonical().with_comments(false);
+    assert!(emit_checked_with(&parsed, &options).is_ok());
+
+    // Rewrapped comments still count as the same comment
+    let options = EmitOptions::new().with_comment_width(10);
+    let mut commented = parsed;
+    commented.get_mut("name").unwrap().leading_comment =
+        Some("The name other services use".to_string());
+    assert!(emit_checked_with(&commented, &options).is_ok());
+}
+
+#[test]
+fn test_reports_first_difference() {
+    let mut node = parse(CONFIG).unwrap();
+    *node.get_path_mut("server.ports.1").unwrap() = string("say \"hi\"");
+
+    let error = emit_checked(&node).unwrap_err();
+    assert_eq!(error.path, "server.ports.1");
+    assert_eq!(error.output, emit(&node));
+    assert!(error
+        .to_string()
+        .starts_with("output doesn't round-trip at `server.ports.1`: "));
+}
+
+#[test]
+fn test_reports_lost_keys() {
+    let mut node = parse("name: api").unwrap();
+    if let YamlValue::Object(map) = &mut node.value {
+        map.insert("key with: colon".to_string(), string("x"));
+    }
+
+    let error = emit_checked(&node).unwrap_err();
+    assert_eq!(error.path, "key with: colon");
+    assert_eq!(error.reason, "key is missing");
+}
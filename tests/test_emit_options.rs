@@ -0,0 +1,200 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit, emit_with, parse, CollectionStyle, EmitOptions, FlowStyle, LineEnding, QuoteStyle,
+    SequenceIndent,
+};
+
+const CONFIG: &str = r#"# Service
+name: api
+server:
+  host: localhost # Bind address
+  ports:
+    - "8080"
+    - "8443"
+  routes:
+    - path: /health
+      method: GET
+"#;
+
+#[test]
+fn test_default_options_match_emit() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+    assert_eq!(emit_with(&parsed, &EmitOptions::default()), emit(&parsed));
+}
+
+#[test]
+fn test_indent_and_flush_sequences() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+    let options = EmitOptions::new()
+        .with_indent(4)
+        .with_sequence_indent(SequenceIndent::Flush);
+
+    let expected = r#"# Service
+name: api
+server:
+    host: localhost # Bind address
+    ports:
+    - "8080"
+    - "8443"
+    routes:
+    - path: /health
+      method: GET"#;
+    let output = emit_with(&parsed, &options);
+    assert_eq!(output, expected);
+    assert_eq!(parse(&output).expect("Failed to reparse"), parsed);
+}
+
+#[test]
+fn test_single_quotes() {
+    let parsed = parse("port: \"8080\"\nquote: 'it\"s'\nempty: \"\"").expect("Failed to parse");
+    let options = EmitOptions::new().with_quote_style(QuoteStyle::Single);
+
+    let output = emit_with(&parsed, &options);
+    assert_eq!(output, "port: '8080'\nquote: 'it\"s'\nempty: ''");
+    assert_eq!(parse(&output).expect("Failed to reparse"), parsed);
+}
+
+#[test]
+fn test_single_quotes_fall_back_to_double() {
+    let parsed = parse("key: \"it's: here\"").expect("Failed to parse");
+    let options = EmitOptions::new().with_quote_style(QuoteStyle::Single);
+
+    assert_eq!(emit_with(&parsed, &options), "key: \"it's: here\"");
+}
+
+#[test]
+fn test_crlf_line_ending() {
+    let parsed = parse("# Top\na: x\nb:\n  - y\n# End").expect("Failed to parse");
+    let options = EmitOptions::new().with_line_ending(LineEnding::CrLf);
+
+    assert_eq!(
+        emit_with(&parsed, &options),
+        "# Top\r\na: x\r\nb:\r\n  - y\r\n# End\r\n"
+    );
+}
+
+#[test]
+fn test_comment_options() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    let compact = emit_with(&parsed, &EmitOptions::new().with_comment_space(false));
+    assert!(compact.starts_with("#Service\n"));
+    assert!(compact.contains("host: localhost #Bind address\n"));
+
+    let stripped = emit_with(&parsed, &EmitOptions::new().with_comments(false));
+    assert!(!stripped.contains('#'));
+    assert!(stripped.starts_with("name: api\nserver:\n  host: localhost\n"));
+}
+
+#[test]
+fn test_line_width_folds_long_strings() {
+    let parsed = parse(
+        "description: Checks the service health every few seconds and restarts it\nnotes:\n  - Runs only on the primary node of each cluster\n",
+    )
+    .expect("Failed to parse");
+    let options = EmitOptions::new().with_line_width(30);
+
+    let expected = r#"description: >-
+  Checks the service health
+  every few seconds and
+  restarts it
+notes:
+  - >-
+    Runs only on the primary
+    node of each cluster"#;
+    let output = emit_with(&parsed, &options);
+    assert_eq!(output, expected);
+    assert_eq!(parse(&output).expect("Failed to reparse"), parsed);
+}
+
+#[test]
+fn test_line_width_keeps_trailing_newline() {
+    let mut parsed = parse("sql: x").expect("Failed to parse");
+    *parsed.get_mut("sql").unwrap() = "SELECT id, name FROM users WHERE active\n".into();
+
+    let output = emit_with(&parsed, &EmitOptions::new().with_line_width(20));
+    assert_eq!(
+        output,
+        "sql: >\n  SELECT id, name\n  FROM users WHERE\n  active"
+    );
+    assert_eq!(parse(&output).expect("Failed to reparse"), parsed);
+}
+
+#[test]
+fn test_line_width_leaves_unfoldable_strings() {
+    let yaml = r#"short: fits on the line
+spaced: "two  spaces  between  every  word"
+symbols: "-- starts with an indicator and runs long"
+commented: a long value that has an inline comment # keep me"#;
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    let output = emit_with(&parsed, &EmitOptions::new().with_line_width(20));
+    assert!(!output.contains('>'));
+    assert!(output.contains("# keep me"));
+}
+
+#[test]
+fn test_flow_style_for_short_collections() {
+    let yaml = r#"matrix:
+  os:
+    - ubuntu
+    - macos
+  rust:
+    - stable
+    - "1.70"
+  env:
+    CI: "true"
+    MODE: fast, safe
+  targets:
+    - x86_64-unknown-linux-gnu
+    - aarch64-unknown-linux-gnu
+  include:
+    - beta # Allowed to fail
+"#;
+    let parsed = parse(yaml).expect("Failed to parse");
+    let options = EmitOptions::new().with_flow_style(FlowStyle::Short { max_width: 40 });
+
+    let expected = r#"matrix:
+  os: [ubuntu, macos]
+  rust: [stable, "1.70"]
+  env: {CI: "true", MODE: "fast, safe"}
+  targets:
+    - x86_64-unknown-linux-gnu
+    - aarch64-unknown-linux-gnu
+  include:
+    - beta # Allowed to fail"#;
+    assert_eq!(emit_with(&parsed, &options), expected);
+}
+
+#[test]
+fn test_collection_style_per_node() {
+    let mut parsed = parse("steps:\n  - a\n  - b\nservices:\n  db:\n    image: postgres\n    ports:\n      - \"5432\"\n")
+        .expect("Failed to parse");
+    let steps = parsed.get_mut("steps").unwrap();
+    *steps = steps.clone().with_style(CollectionStyle::Block);
+    let db = parsed.get_path_mut("services.db").unwrap();
+    *db = db.clone().with_style(CollectionStyle::Flow);
+
+    let options = EmitOptions::new().with_flow_style(FlowStyle::Short { max_width: 80 });
+    assert_eq!(
+        emit_with(&parsed, &options),
+        "steps:\n  - a\n  - b\nservices:\n  db: {image: postgres, ports: [\"5432\"]}"
+    );
+
+    // A whole document can be written in flow style too
+    let root = parse("a: b\nc:\n  - d").expect("Failed to parse");
+    let root = root.with_style(CollectionStyle::Flow);
+    assert_eq!(emit(&root), "{a: b, c: [d]}");
+}
+
+#[test]
+fn test_flow_style_keeps_comments() {
+    let parsed = parse("ports:\n  # HTTP\n  - \"80\"\n  - \"443\"").expect("Failed to parse");
+    let ports = parsed.get("ports").unwrap().clone();
+    let mut forced = parsed.clone();
+    *forced.get_mut("ports").unwrap() = ports.with_style(CollectionStyle::Flow);
+
+    // Flow style has nowhere to put the comment, so the block form is kept
+    assert_eq!(emit(&forced), emit(&parsed));
+}
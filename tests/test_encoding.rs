@@ -0,0 +1,57 @@
+#![deny(clippy::all)]
+
+use yamp::{emit_bytes, parse, parse_bytes, parse_document, Encoding, LineEnding};
+
+const CONFIG: &str = "# Vendor export\r\nname: Zoë 🚀\r\nitems:\r\n  - a\r\n  - b\r\n";
+
+#[test]
+fn test_parse_bytes_in_every_encoding() {
+    let expected = parse(CONFIG).unwrap();
+    for encoding in [
+        Encoding::Utf8,
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+        Encoding::Utf32Le,
+        Encoding::Utf32Be,
+    ] {
+        for bom in [false, true] {
+            let bytes = encoding.encode(CONFIG, bom);
+            assert_eq!(Encoding::detect(&bytes), (encoding, bom));
+
+            let document = parse_bytes(&bytes).unwrap();
+            assert_eq!(document.encoding, encoding);
+            assert_eq!(document.bom, bom);
+            assert_eq!(document.line_ending, LineEnding::CrLf);
+            assert_eq!(document.root, expected, "{:?} bom={}", encoding, bom);
+
+            // Written back in the same encoding, BOM and line endings
+            let output = emit_bytes(&document);
+            assert_eq!(Encoding::detect(&output), (encoding, bom));
+            assert_eq!(parse_bytes(&output).unwrap(), document);
+        }
+    }
+}
+
+#[test]
+fn test_utf8_bom_is_not_content() {
+    let parsed = parse("\u{FEFF}name: api").unwrap();
+    assert_eq!(parsed.get("name").and_then(|n| n.as_str()), Some("api"));
+
+    let document = parse_document("\u{FEFF}name: api").unwrap();
+    assert!(document.bom);
+    assert_eq!(emit_bytes(&document), b"\xEF\xBB\xBFname: api");
+}
+
+#[test]
+fn test_invalid_bytes() {
+    assert_eq!(
+        parse_bytes(b"name: \xFF").unwrap_err(),
+        "Invalid UTF-8 at byte 6"
+    );
+    // An unpaired surrogate
+    assert_eq!(
+        parse_bytes(b"n\x00:\x00 \x00\x00\xD8").unwrap_err(),
+        "Invalid UTF-16 at byte 6"
+    );
+    assert!(parse_bytes(b"n\x00:\x00 ").is_err());
+}
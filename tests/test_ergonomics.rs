@@ -156,8 +156,7 @@ app:
         .and_then(|ssl| ssl.get("cert"))
         .and_then(|c| c.as_str());
 
-    // Note: The lexer currently strips the leading slash
-    assert_eq!(cert_path, Some("path/to/cert"));
+    assert_eq!(cert_path, Some("/path/to/cert"));
 }
 
 #[test]
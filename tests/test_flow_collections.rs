@@ -0,0 +1,151 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit, emit_checked_with, emit_with, parse, CollectionStyle, EmitOptions, FlowStyle, YamlNode,
+};
+
+fn strings(node: Option<&YamlNode>) -> Vec<&str> {
+    node.and_then(|n| n.as_array())
+        .map(|items| items.iter().filter_map(|item| item.as_str()).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_workflow_flow_sequences() {
+    let workflow = r#"on:
+  push:
+    branches: [ main ]
+jobs:
+  test:
+    strategy:
+      matrix:
+        os: [ubuntu-latest, windows-latest, macos-latest] # Every platform
+        rust: [stable, beta, nightly]
+"#;
+    let parsed = parse(workflow).unwrap();
+    assert_eq!(strings(parsed.get_path("on.push.branches")), vec!["main"]);
+    let os = parsed.get_path("jobs.test.strategy.matrix.os").unwrap();
+    assert_eq!(os.style, CollectionStyle::Flow);
+    assert_eq!(os.inline_comment.as_deref(), Some("Every platform"));
+    assert_eq!(
+        strings(Some(os)),
+        vec!["ubuntu-latest", "windows-latest", "macos-latest"]
+    );
+
+    // Written back in flow style
+    let output = emit(&parsed);
+    assert!(output.contains("    branches: [main]\n"));
+    assert!(output.contains("os: [ubuntu-latest, windows-latest, macos-latest] # Every platform"));
+
+    let hell = std::fs::read_to_string("sample_yamls/document_from_hell.yaml").unwrap();
+    let parsed = parse(&hell).unwrap();
+    let on = parsed.get_path("server_config.flush_cache.on");
+    assert_eq!(strings(on), vec!["push", "memory_pressure"]);
+}
+
+#[test]
+fn test_nested_flow_mappings() {
+    let parsed =
+        parse(r#"db: {host: localhost, ports: ["5432", 5433], "opts": {ssl: on}, note: "a, b"}"#)
+            .unwrap();
+    let db = parsed.get("db").unwrap();
+    assert_eq!(db.style, CollectionStyle::Flow);
+    assert_eq!(db.get("host").and_then(|n| n.as_str()), Some("localhost"));
+    assert_eq!(strings(db.get("ports")), vec!["5432", "5433"]);
+    assert_eq!(db.get_path("opts.ssl").and_then(|n| n.as_str()), Some("on"));
+    assert_eq!(db.get("note").and_then(|n| n.as_str()), Some("a, b"));
+
+    // Single pairs in a sequence, empty values and empty collections
+    let parsed = parse("- [name: web, port]\n- {debug, level:}\n- []\n- {}").unwrap();
+    let items = parsed.as_array().unwrap();
+    assert_eq!(
+        items[0].get_path("0.name").and_then(|n| n.as_str()),
+        Some("web")
+    );
+    assert!(items[1].get("debug").is_some_and(YamlNode::is_blank));
+    assert!(items[1].get("level").is_some_and(YamlNode::is_blank));
+    assert_eq!(items[2].as_array().map(Vec::len), Some(0));
+    assert_eq!(items[3].as_object().map(|o| o.len()), Some(0));
+    assert_eq!(
+        emit(&parsed),
+        "- [{name: web}, port]\n- {debug, level}\n- []\n- {}"
+    );
+}
+
+#[test]
+fn test_multi_line_flow_with_comments() {
+    let yaml = r#"services:
+  web:
+    ports: [
+      # Public
+      "80", "443", # TLS
+      8080 with
+        a long name,
+    ]
+    image: nginx
+"#;
+    let parsed = parse(yaml).unwrap();
+    let ports = parsed.get_path("services.web.ports").unwrap();
+    assert_eq!(
+        strings(Some(ports)),
+        vec!["80", "443", "8080 with a long name"]
+    );
+    assert_eq!(
+        ports.get_path("0").unwrap().leading_comment.as_deref(),
+        Some("Public")
+    );
+    assert_eq!(
+        ports.get_path("1").unwrap().inline_comment.as_deref(),
+        Some("TLS")
+    );
+    assert_eq!(
+        parsed
+            .get_path("services.web.image")
+            .and_then(|n| n.as_str()),
+        Some("nginx")
+    );
+
+    // Flow style has nowhere to put the comments, so they're written in block style
+    let reparsed = parse(&emit(&parsed)).unwrap();
+    assert_eq!(
+        strings(reparsed.get_path("services.web.ports")),
+        strings(Some(ports))
+    );
+}
+
+#[test]
+fn test_short_flow_output_round_trips() {
+    let parsed = parse(
+        "name: api\ntags:\n  - web\n  - \"8080\"\n  - \"a, b\"\nlimits:\n  cpu: \"1\"\n  memory: 512Mi\n",
+    )
+    .unwrap();
+    let options = EmitOptions::new().with_flow_style(FlowStyle::Short { max_width: 80 });
+    let output = emit_checked_with(&parsed, &options).unwrap();
+    assert_eq!(
+        output,
+        "name: api\ntags: [web, \"8080\", \"a, b\"]\nlimits: {cpu: \"1\", memory: 512Mi}"
+    );
+
+    // Parsed flow collections keep their style
+    let reparsed = parse(&output).unwrap();
+    assert_eq!(emit(&reparsed), output);
+    assert_eq!(
+        emit_with(&reparsed, &EmitOptions::canonical()),
+        emit_with(&parsed, &EmitOptions::canonical())
+    );
+}
+
+#[test]
+fn test_flow_errors() {
+    assert_eq!(
+        parse("tags: [a, b").unwrap_err(),
+        "Unterminated flow sequence"
+    );
+    assert_eq!(
+        parse("tags: {a: b").unwrap_err(),
+        "Unterminated flow mapping"
+    );
+    assert!(parse("tags: [a b] c").is_err());
+    assert!(parse("tags: [a, , b]").is_err());
+    assert!(parse("tags: {[a]: b}").is_err());
+}
@@ -0,0 +1,105 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit, emit_fragment, emit_fragment_with, parse, parse_fragment, CollectionStyle, EmitOptions,
+    YamlNode, YamlValue,
+};
+
+const SERVICE: &str = r#"# Web container
+image: nginx # Pinned
+ports:
+  - "80"
+script: |
+  nginx -t
+  nginx
+"#;
+
+#[test]
+fn test_emit_fragment_indents_every_line() {
+    let parsed = parse(SERVICE).unwrap();
+    let fragment = emit_fragment(&parsed, 4);
+    assert_eq!(
+        fragment,
+        "    # Web container\n    image: nginx # Pinned\n    ports:\n      - \"80\"\n    script: |\n      nginx -t\n      nginx"
+    );
+
+    // Embedded under a key, the fragment reads back as its value
+    let file = format!("services:\n  web:\n{}", fragment);
+    let embedded = parse(&file).unwrap();
+    assert_eq!(
+        embedded
+            .get("services")
+            .and_then(|s| s.get("web"))
+            .unwrap()
+            .value,
+        parsed.value
+    );
+
+    // Without indentation it matches a document
+    assert_eq!(emit_fragment(&parsed, 0), emit(&parsed));
+}
+
+#[test]
+fn test_fragment_root_comments() {
+    // A scalar keeps its inline comment on the same line
+    let scalar = YamlNode::from_value(YamlValue::String("nginx".into()))
+        .with_leading_comment("Image")
+        .with_inline_comment("Pinned");
+    assert_eq!(emit_fragment(&scalar, 2), "  # Image\n  nginx # Pinned");
+
+    // A block collection has no line of its own, so it's written after it
+    let mut list = parse("- a\n- b").unwrap();
+    list.inline_comment = Some("Trailing".into());
+    assert_eq!(emit_fragment(&list, 2), "  - a\n  - b\n  # Trailing\n");
+
+    let flow = list.with_style(CollectionStyle::Flow);
+    assert_eq!(emit_fragment(&flow, 2), "  [a, b] # Trailing");
+}
+
+#[test]
+fn test_emit_fragment_with_options() {
+    let parsed = parse("ports:\n  - \"80\"").unwrap();
+    let options = EmitOptions::new().with_indent(4);
+    assert_eq!(
+        emit_fragment_with(&parsed, 2, &options),
+        "  ports:\n      - \"80\""
+    );
+}
+
+#[test]
+fn test_parse_fragment_removes_common_indent() {
+    let snippet = "      image: nginx # Pinned\n\n      ports:\n        - \"80\"\n      script: |\n        nginx -t\n\n        nginx\n";
+    let parsed = parse_fragment(snippet).unwrap();
+    assert_eq!(parsed.get("image").and_then(|n| n.as_str()), Some("nginx"));
+    assert_eq!(
+        parsed.get("script").and_then(|n| n.as_str()),
+        Some("nginx -t\n\nnginx\n")
+    );
+
+    // Round trip through an indented fragment
+    let original = parse(SERVICE).unwrap();
+    assert_eq!(
+        parse_fragment(&emit_fragment(&original, 6)).unwrap(),
+        original
+    );
+
+    // Unindented input is parsed as is
+    assert_eq!(parse_fragment(SERVICE).unwrap(), original);
+}
+
+#[test]
+fn test_fragment_keeps_trailing_comments() {
+    let parsed = parse("server:\n  host: x\n  # port: 80\n# End of server").unwrap();
+
+    // Written as emit does, in the blocks they were in
+    let fragment = emit_fragment(&parsed, 4);
+    assert_eq!(
+        fragment,
+        "    server:\n      host: x\n      # port: 80\n    # End of server\n"
+    );
+    assert_eq!(parse_fragment(&fragment).unwrap(), parsed);
+    assert_eq!(
+        parse_fragment(&fragment).unwrap().commented_entries()[0].path,
+        "server.port"
+    );
+}
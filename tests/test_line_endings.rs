@@ -0,0 +1,80 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit_document, emit_with, parse, parse_document, Document, LineEnding, YamlNode, YamlValue,
+};
+
+const CONFIG: &str = r#"# Service
+name: api # Public name
+script: |
+  cargo build
+  cargo test
+summary: >-
+  Builds and
+  tests
+hosts:
+  - "a.example.com"
+  - b.example.com
+"#;
+
+#[test]
+fn test_crlf_parses_like_lf() {
+    let lf = parse(CONFIG).unwrap();
+    let crlf = parse(&CONFIG.replace('\n', "\r\n")).unwrap();
+    assert_eq!(crlf, lf);
+
+    // Nothing keeps a stray `\r`
+    assert_eq!(crlf.get("name").and_then(|n| n.as_str()), Some("api"));
+    assert_eq!(
+        crlf.get("name").unwrap().inline_comment.as_deref(),
+        Some("Public name")
+    );
+    assert_eq!(
+        crlf.get("script").and_then(|n| n.as_str()),
+        Some("cargo build\ncargo test\n")
+    );
+    assert_eq!(
+        crlf.get("summary").and_then(|n| n.as_str()),
+        Some("Builds and tests")
+    );
+}
+
+#[test]
+fn test_lone_carriage_returns_break_lines() {
+    let parsed = parse("a: 1\rb: 2\r").unwrap();
+    assert_eq!(parsed.get("a").and_then(|n| n.as_str()), Some("1"));
+    assert_eq!(parsed.get("b").and_then(|n| n.as_str()), Some("2"));
+}
+
+#[test]
+fn test_detect_line_ending() {
+    assert_eq!(LineEnding::detect("a: 1\r\nb: 2"), LineEnding::CrLf);
+    assert_eq!(LineEnding::detect("a: 1\nb: 2\r\n"), LineEnding::Lf);
+    assert_eq!(LineEnding::detect("a: 1"), LineEnding::Lf);
+}
+
+#[test]
+fn test_document_keeps_line_ending() {
+    let source = CONFIG.replace('\n', "\r\n");
+    let document = parse_document(&source).unwrap();
+    assert_eq!(document.line_ending, LineEnding::CrLf);
+
+    let output = emit_document(&document);
+    assert!(!output.replace("\r\n", "").contains('\n'), "{:?}", output);
+    assert_eq!(parse(&output).unwrap(), document.root);
+
+    // Other settings can be changed while keeping the convention
+    let options = document.emit_options().with_comments(false);
+    assert!(emit_with(&document.root, &options).starts_with("name: api\r\nscript: |\r\n"));
+
+    let lf = parse_document(CONFIG).unwrap();
+    assert_eq!(lf.line_ending, LineEnding::Lf);
+    assert_eq!(emit_document(&lf), yamp::emit(&lf.root));
+}
+
+#[test]
+fn test_new_document_uses_lf() {
+    let document = Document::new(YamlNode::from_value(YamlValue::String("x".into())));
+    assert_eq!(document.line_ending, LineEnding::Lf);
+    assert_eq!(emit_document(&document), "x");
+}
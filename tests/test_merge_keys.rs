@@ -0,0 +1,121 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, parse, parse_with, resolve_merge_keys, AliasMode, ParseOptions, YamlNode};
+
+fn merged(yaml: &str, aliases: AliasMode) -> YamlNode {
+    let options = ParseOptions::new().with_aliases(aliases);
+    let mut parsed = parse_with(yaml, &options).unwrap();
+    resolve_merge_keys(&mut parsed).unwrap();
+    parsed
+}
+
+fn keys(node: Option<&YamlNode>) -> Vec<&str> {
+    node.and_then(YamlNode::as_object)
+        .map(|map| map.keys().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+const SERVICES: &str = r#"x-defaults: &defaults
+  restart: always
+  # Logs go to the collector
+  logging: {driver: fluentd}
+  replicas: "1"
+x-large: &large
+  replicas: "4"
+  memory: 4g
+services:
+  web:
+    image: web
+    # Shared settings
+    <<: *defaults
+    replicas: "2" # Local wins
+  db:
+    <<: [*large, *defaults]
+"#;
+
+#[test]
+fn test_merge_single_mapping() {
+    for mode in [
+        AliasMode::Preserve,
+        AliasMode::Expand { max_expansion: 100 },
+    ] {
+        let parsed = merged(SERVICES, mode);
+        let web = parsed.get_path("services.web");
+        assert_eq!(keys(web), vec!["image", "restart", "logging", "replicas"]);
+        let web = web.unwrap();
+        assert_eq!(web.get("replicas").and_then(YamlNode::as_str), Some("2"));
+        assert_eq!(
+            web.get_path("logging.driver").and_then(YamlNode::as_str),
+            Some("fluentd")
+        );
+
+        // The merge key's comment moves to the first merged entry, and
+        // merged entries keep their own
+        let restart = web.get("restart").unwrap();
+        assert_eq!(restart.leading_comment.as_deref(), Some("Shared settings"));
+        let logging = web.get("logging").unwrap();
+        assert_eq!(
+            logging.leading_comment.as_deref(),
+            Some("Logs go to the collector")
+        );
+        assert_eq!(
+            web.get("replicas").unwrap().inline_comment.as_deref(),
+            Some("Local wins")
+        );
+    }
+}
+
+#[test]
+fn test_merge_sequence_in_order() {
+    let parsed = merged(SERVICES, AliasMode::Preserve);
+    let db = parsed.get_path("services.db");
+    assert_eq!(keys(db), vec!["replicas", "memory", "restart", "logging"]);
+    assert_eq!(
+        db.and_then(|db| db.get("replicas"))
+            .and_then(YamlNode::as_str),
+        Some("4")
+    );
+
+    // Merged copies don't redefine anchors, and the sources stay as they were
+    let output = emit(&parsed);
+    assert_eq!(output.matches("&defaults").count(), 1);
+    assert!(!output.contains("<<"));
+    assert_eq!(keys(parsed.get("x-large")), vec!["replicas", "memory"]);
+}
+
+#[test]
+fn test_nested_and_inline_merges() {
+    let yaml = r#"base: &base
+  a: "1"
+middle: &middle
+  <<: *base
+  b: "2"
+top:
+  <<: *middle
+  c: "3"
+inline:
+  <<: {x: y}
+"#;
+    let parsed = merged(yaml, AliasMode::Preserve);
+    assert_eq!(keys(parsed.get("top")), vec!["a", "b", "c"]);
+    assert_eq!(keys(parsed.get("inline")), vec!["x"]);
+
+    // Merging is opt-in: without it `<<` is an ordinary key
+    let parsed = parse("inline:\n  <<: {x: y}").unwrap();
+    assert_eq!(keys(parsed.get("inline")), vec!["<<"]);
+}
+
+#[test]
+fn test_merge_errors() {
+    let options = ParseOptions::new().with_aliases(AliasMode::Preserve);
+    let mut parsed = parse_with("a: &a text\nb:\n  <<: *a", &options).unwrap();
+    let original = parsed.clone();
+    assert_eq!(
+        resolve_merge_keys(&mut parsed).unwrap_err(),
+        "Merge key at b.<< must be a mapping, an alias to one, or a sequence of them"
+    );
+    assert_eq!(parsed, original);
+
+    let mut parsed = parse("a:\n  <<: [{x: y}, z]").unwrap();
+    assert!(resolve_merge_keys(&mut parsed).is_err());
+}
@@ -0,0 +1,177 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit_document, emit_documents, emit_documents_bytes, parse, parse_document, parse_documents,
+    parse_documents_bytes, Encoding, LineEnding, YamlNode,
+};
+
+const BUNDLE: &str = r#"# Generated manifests
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+---
+# The web deployment
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  replicas: "2"
+"#;
+
+fn kind(document: &yamp::Document) -> Option<&str> {
+    document.root.get("kind").and_then(|k| k.as_str())
+}
+
+#[test]
+fn test_parse_bundle() {
+    let documents = parse_documents(BUNDLE).unwrap();
+    assert_eq!(documents.len(), 2);
+    assert_eq!(kind(&documents[0]), Some("Service"));
+    assert_eq!(kind(&documents[1]), Some("Deployment"));
+    assert!(documents
+        .iter()
+        .all(|d| d.explicit_start && !d.explicit_end));
+
+    // Comments before the first marker are the document's header, and
+    // comments after a marker go with the document's content
+    assert_eq!(
+        documents[0].header_comment.as_deref(),
+        Some("Generated manifests")
+    );
+    assert_eq!(documents[1].header_comment, None);
+    let api_version = documents[1].root.get("apiVersion").unwrap();
+    assert_eq!(
+        api_version.leading_comment.as_deref(),
+        Some("The web deployment")
+    );
+}
+
+#[test]
+fn test_markers_with_content_and_end() {
+    let stream = "--- # First\nname: a\n...\n# Between\n...\n--- b\n...\n";
+    let documents = parse_documents(stream).unwrap();
+    assert_eq!(documents.len(), 2);
+    assert!(documents[0].explicit_end);
+    assert_eq!(
+        documents[0]
+            .root
+            .get("name")
+            .unwrap()
+            .leading_comment
+            .as_deref(),
+        Some("First")
+    );
+    assert_eq!(documents[1].root.as_str(), Some("b"));
+    assert!(documents[1].explicit_start && documents[1].explicit_end);
+
+    // Streams without markers or content have no documents
+    assert!(parse_documents("").unwrap().is_empty());
+    assert!(parse_documents("# Nothing\n").unwrap().is_empty());
+}
+
+#[test]
+fn test_header_comment_round_trip() {
+    let yaml = "# License\n---\na: \"1\"";
+    let documents = parse_documents(yaml).unwrap();
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0].header_comment.as_deref(), Some("License"));
+    assert_eq!(documents[0].root.get("a").unwrap().leading_comment, None);
+    assert_eq!(emit_documents(&documents), yaml);
+
+    // A header after a document needs that document to end with `...`
+    let stream = "a: b\n...\n# Second\n#\n# Notes\n%YAML 1.2\n---\nc: d";
+    let mut documents = parse_documents(stream).unwrap();
+    assert_eq!(
+        documents[1].header_comment.as_deref(),
+        Some("Second\n\nNotes")
+    );
+    assert_eq!(emit_documents(&documents), stream);
+    documents[0].explicit_end = false;
+    assert_eq!(emit_documents(&documents), stream);
+}
+
+#[test]
+fn test_empty_documents_round_trip() {
+    let documents = parse_documents("---\n---").unwrap();
+    assert_eq!(documents.len(), 2);
+    assert!(documents.iter().all(|d| d.root == YamlNode::blank()));
+    assert_eq!(emit_documents(&documents), "---\n---");
+
+    let stream = "a: b\n---\n# Nothing\n...\n---\nc: d";
+    let documents = parse_documents(stream).unwrap();
+    assert_eq!(documents.len(), 3);
+    assert_eq!(documents[1].root.value, YamlNode::blank().value);
+    assert_eq!(documents[1].root.inline_comment.as_deref(), Some("Nothing"));
+    assert_eq!(emit_documents(&documents), stream);
+}
+
+#[test]
+fn test_block_scalar_on_marker_line() {
+    let documents =
+        parse_documents("--- |\n  line one\n  line two\n--- >-\n  folded\n  text\n").unwrap();
+    assert_eq!(documents[0].root.as_str(), Some("line one\nline two\n"));
+    assert_eq!(documents[1].root.as_str(), Some("folded text"));
+    assert_eq!(
+        parse_documents(&emit_documents(&documents)).unwrap(),
+        documents
+    );
+}
+
+#[test]
+fn test_documents_bytes() {
+    let bytes = Encoding::Utf16Be.encode("a: b\n---\nc: d", true);
+    let documents = parse_documents_bytes(&bytes).unwrap();
+    assert_eq!(documents.len(), 2);
+    assert!(documents.iter().all(|d| d.encoding == Encoding::Utf16Be));
+    assert!(documents[0].bom && !documents[1].bom);
+    assert_eq!(emit_documents_bytes(&documents), bytes);
+    assert!(emit_documents_bytes(&[]).is_empty());
+}
+
+#[test]
+fn test_emit_round_trip() {
+    let documents = parse_documents(BUNDLE).unwrap();
+    let output = emit_documents(&documents);
+    assert_eq!(
+        output,
+        r#"# Generated manifests
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+---
+# The web deployment
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  replicas: "2""#
+    );
+    assert_eq!(parse_documents(&output).unwrap(), documents);
+
+    // Every document after the first gets a marker, with its line ending
+    let mut documents = parse_documents("a: 1\r\n---\r\nb: 2\r\n...\r\n").unwrap();
+    assert_eq!(documents[1].line_ending, LineEnding::CrLf);
+    documents[1].explicit_start = false;
+    assert_eq!(
+        emit_documents(&documents),
+        "a: \"1\"\r\n---\r\nb: \"2\"\r\n..."
+    );
+    assert_eq!(emit_documents(&[]), "");
+}
+
+#[test]
+fn test_single_document_functions() {
+    let document = parse_document("---\nname: api\n...\n").unwrap();
+    assert_eq!(emit_document(&document), "---\nname: api\n...");
+    assert_eq!(parse("---\nname: api").unwrap(), document.root);
+
+    let error = parse(BUNDLE).unwrap_err();
+    assert!(error.contains("found 2"), "{}", error);
+    assert_eq!(parse("# Nothing").unwrap_err(), "Unexpected end of input");
+
+    // Errors say which document failed
+    let error = parse_documents("a: b\n---\n: c\n").unwrap_err();
+    assert!(error.starts_with("Document 2: "), "{}", error);
+}
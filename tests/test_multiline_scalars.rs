@@ -0,0 +1,102 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, parse, YamlNode};
+
+fn text<'n>(root: &'n YamlNode, path: &str) -> Option<&'n str> {
+    root.get_path(path).and_then(YamlNode::as_str)
+}
+
+const VALUES: &str = r#"image:
+  repository: nginx
+  # Shown in the chart listing
+  description: Serves the static site and proxies
+    API calls to the backend.
+
+
+    Scales with the ingress.
+  pullPolicy: IfNotPresent
+tasks:
+  - Restart the service after
+    the config changes
+  - name: Check
+    shell: echo done
+"#;
+
+#[test]
+fn test_plain_scalar_continuation() {
+    let parsed = parse(VALUES).unwrap();
+    assert_eq!(
+        text(&parsed, "image.description"),
+        Some("Serves the static site and proxies API calls to the backend.\n\nScales with the ingress.")
+    );
+    let description = parsed.get_path("image.description").unwrap();
+    assert_eq!(
+        description.leading_comment.as_deref(),
+        Some("Shown in the chart listing")
+    );
+    assert_eq!(text(&parsed, "image.pullPolicy"), Some("IfNotPresent"));
+    assert_eq!(
+        text(&parsed, "tasks.0"),
+        Some("Restart the service after the config changes")
+    );
+    assert_eq!(text(&parsed, "tasks.1.shell"), Some("echo done"));
+
+    // A comment ends the scalar and stays on it
+    let parsed = parse("key: one\n  two # note\n  # next\nnext: x").unwrap();
+    assert_eq!(text(&parsed, "key"), Some("one two"));
+    let key = parsed.get("key").unwrap();
+    assert_eq!(key.inline_comment.as_deref(), Some("note"));
+    assert_eq!(text(&parsed, "next"), Some("x"));
+}
+
+#[test]
+fn test_quoted_scalar_folding() {
+    let yaml = r#"double: "first
+  second
+
+  third "
+single: 'it''s
+    folded'
+joined: "no \
+  space"
+flow: ["a
+  b", 'c']
+after: x
+"#;
+    let parsed = parse(yaml).unwrap();
+    assert_eq!(text(&parsed, "double"), Some("first second\nthird "));
+    assert_eq!(text(&parsed, "single"), Some("it''s folded"));
+    assert_eq!(text(&parsed, "joined"), Some("no space"));
+    assert_eq!(text(&parsed, "flow.0"), Some("a b"));
+    assert_eq!(text(&parsed, "after"), Some("x"));
+}
+
+#[test]
+fn test_folded_scalars_round_trip() {
+    let parsed = parse(VALUES).unwrap();
+    let output = emit(&parsed);
+    assert!(
+        output.contains("  - Restart the service after the config changes\n"),
+        "{}",
+        output
+    );
+    assert_eq!(parse(&output).unwrap(), parsed);
+}
+
+#[test]
+fn test_unterminated_quoted_scalar() {
+    for yaml in [
+        "a: 'aué",
+        "a: \"line\\\"",
+        "a: 'it''",
+        "- 'a\n  b",
+        "'key: value",
+    ] {
+        assert_eq!(
+            parse(yaml),
+            Err("Unterminated quoted string".to_string()),
+            "{:?}",
+            yaml
+        );
+    }
+}
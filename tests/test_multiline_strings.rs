@@ -24,7 +24,7 @@ other: value
     // Check that the description values match
     let map1 = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::Object for parsed, got {:?}",
                 parsed.value
@@ -33,7 +33,7 @@ other: value
     };
     let map2 = match &reparsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::Object for reparsed, got {:?}",
                 reparsed.value
@@ -66,14 +66,14 @@ fn test_quoted_string_with_escaped_newline() {
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
@@ -94,24 +94,24 @@ and even a third line""#;
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
             )
         }
     };
-    // Actual newlines in quoted strings should be preserved
+    // Line breaks in quoted strings fold into spaces
     assert_eq!(
         s.as_str(),
-        "This is a string\nthat continues on the next line\nand even a third line"
+        "This is a string that continues on the next line and even a third line"
     );
 }
 
@@ -128,14 +128,14 @@ description: |
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
@@ -161,14 +161,14 @@ description: >
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
@@ -195,14 +195,14 @@ description: |-
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
@@ -226,23 +226,22 @@ description: |+
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
             )
         }
     };
-    // Note: our current implementation doesn't capture trailing blank lines
-    // This is a known limitation we can improve later
-    assert_eq!(s.as_str(), "Line 1\nLine 2\n")
+    // The trailing blank line is kept
+    assert_eq!(s.as_str(), "Line 1\nLine 2\n\n")
 }
 
 #[test]
@@ -259,14 +258,14 @@ description: >-
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let description_value = map.get("description").expect("description key not found");
     let s = match &description_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for description, got {:?}",
                 description_value.value
@@ -289,14 +288,14 @@ And YAMP is too!"
 
     let map = match &parsed.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object, got {:?}", parsed.value)
         }
     };
     let poem_value = map.get("poem").expect("poem key not found");
     let s = match &poem_value.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!(
                 "Expected YamlValue::String for poem, got {:?}",
                 poem_value.value
@@ -305,6 +304,32 @@ And YAMP is too!"
     };
     assert_eq!(
         s.as_str(),
-        "Roses are red,\nViolets are blue,\nYAML is simple,\nAnd YAMP is too!"
+        "Roses are red, Violets are blue, YAML is simple, And YAMP is too!"
     );
 }
+
+#[test]
+fn test_block_scalar_raw_content() {
+    let yaml = r#"steps:
+  - run: |
+      echo "a: b" # not a comment
+      [ -f x ] && { cat x; }
+        indented
+    name: check
+  - >
+    folded
+    text
+after: done
+"#;
+
+    let parsed = parse(yaml).expect("Failed to parse");
+
+    let steps = parsed.get("steps").and_then(|s| s.as_array()).unwrap();
+    assert_eq!(
+        steps[0].get("run").and_then(|r| r.as_str()),
+        Some("echo \"a: b\" # not a comment\n[ -f x ] && { cat x; }\n  indented\n")
+    );
+    assert_eq!(steps[0].get("name").and_then(|n| n.as_str()), Some("check"));
+    assert_eq!(steps[1].as_str(), Some("folded text\n"));
+    assert_eq!(parsed.get("after").and_then(|a| a.as_str()), Some("done"));
+}
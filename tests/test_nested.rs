@@ -42,7 +42,7 @@ level1:
 
     let map = match &result.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object at root, got {:?}", result.value)
         }
     };
@@ -50,7 +50,7 @@ level1:
     let l1 = map.get("level1").expect("Key 'level1' not found in map");
     let l1_map = match &l1.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object for level1, got {:?}", l1.value)
         }
     };
@@ -60,7 +60,7 @@ level1:
         .expect("Key 'level2' not found in level1");
     let l2_map = match &l2.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object for level2, got {:?}", l2.value)
         }
     };
@@ -70,7 +70,7 @@ level1:
         .expect("Key 'level3' not found in level2");
     let l3_map = match &l3.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object for level3, got {:?}", l3.value)
         }
     };
@@ -80,7 +80,7 @@ level1:
         .expect("Key 'value' not found in level3");
     let s = match &val.value {
         YamlValue::String(s) => s,
-        YamlValue::Object(_) | YamlValue::Array(_) => {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::String for value, got {:?}", val.value)
         }
     };
@@ -103,7 +103,7 @@ config:
 
     let map = match &result.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => {
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
             panic!("Expected YamlValue::Object at root, got {:?}", result.value)
         }
     };
@@ -111,10 +111,12 @@ config:
     let config = map.get("config").expect("Key 'config' not found in map");
     let config_map = match &config.value {
         YamlValue::Object(m) => m,
-        YamlValue::String(_) | YamlValue::Array(_) => panic!(
-            "Expected YamlValue::Object for config, got {:?}",
-            config.value
-        ),
+        YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+            panic!(
+                "Expected YamlValue::Object for config, got {:?}",
+                config.value
+            )
+        }
     };
     assert!(config_map.contains_key("features"));
 
@@ -124,10 +126,12 @@ config:
         .expect("Key 'features' not found in config");
     let features_arr = match &features.value {
         YamlValue::Array(arr) => arr,
-        YamlValue::String(_) | YamlValue::Object(_) => panic!(
-            "Expected YamlValue::Array for features, got {:?}",
-            features.value
-        ),
+        YamlValue::String(_) | YamlValue::Object(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+            panic!(
+                "Expected YamlValue::Array for features, got {:?}",
+                features.value
+            )
+        }
     };
     assert_eq!(features_arr.len(), 2);
 }
@@ -0,0 +1,144 @@
+#![deny(clippy::all)]
+
+use yamp::{
+    emit, emit_with, parse, AlwaysQuote, Conservative, EmitOptions, FlowStyle, Minimal,
+    QuotingPolicy, ScalarStyle, YamlNode, YamlValue,
+};
+
+const FLAGS: &str = "enabled: yes\nmode: off\nport: 8080\nid: 0x1F\ndate: 2024-01-31\nname: api";
+
+#[test]
+fn test_default_policy_is_unchanged() {
+    let parsed = parse(FLAGS).unwrap();
+    assert_eq!(
+        emit(&parsed),
+        "enabled: yes\nmode: off\nport: \"8080\"\nid: 0x1F\ndate: 2024-01-31\nname: api"
+    );
+}
+
+#[test]
+fn test_builtin_policies() {
+    let parsed = parse(FLAGS).unwrap();
+
+    let conservative = EmitOptions::new().with_quoting(Conservative);
+    assert_eq!(
+        emit_with(&parsed, &conservative),
+        "enabled: \"yes\"\nmode: \"off\"\nport: \"8080\"\nid: \"0x1F\"\ndate: \"2024-01-31\"\nname: api"
+    );
+
+    let minimal = EmitOptions::new().with_quoting(Minimal);
+    assert_eq!(
+        emit_with(&parsed, &minimal),
+        "enabled: yes\nmode: off\nport: 8080\nid: 0x1F\ndate: 2024-01-31\nname: api"
+    );
+
+    let always = EmitOptions::new().with_quoting(AlwaysQuote);
+    assert_eq!(
+        emit_with(&parse("name: api").unwrap(), &always),
+        "\"name\": \"api\""
+    );
+}
+
+#[test]
+fn test_minimal_still_quotes_what_it_must() {
+    let options = EmitOptions::new().with_quoting(Minimal);
+    for value in [
+        "",
+        " padded",
+        "- item",
+        "--",
+        "key: value",
+        "ends:",
+        "a #b",
+        "*ref",
+        "line\n  \nbreak",
+    ] {
+        let mut node = parse("value: x").unwrap();
+        *node.get_mut("value").unwrap() = YamlNode::from_value(YamlValue::String(value.into()));
+        let output = emit_with(&node, &options);
+        assert!(output.starts_with("value: \""), "{:?} -> {}", value, output);
+    }
+
+    // Plain values that only look special stay plain
+    let parsed = parse("url: https://example.com\ntime: 12:30\nflag: -v").unwrap();
+    assert_eq!(
+        emit_with(&parsed, &options),
+        "url: https://example.com\ntime: 12:30\nflag: -v"
+    );
+}
+
+#[test]
+fn test_custom_policy() {
+    #[derive(Debug)]
+    struct QuoteKeysNamedId;
+
+    impl QuotingPolicy for QuoteKeysNamedId {
+        fn needs_quotes(&self, value: &str) -> bool {
+            value == "id"
+        }
+    }
+
+    let parsed = parse("id: id\nname: api").unwrap();
+    let options = EmitOptions::new().with_quoting(QuoteKeysNamedId);
+    assert_eq!(emit_with(&parsed, &options), "\"id\": \"id\"\nname: api");
+}
+
+#[test]
+fn test_style_overrides_by_path() {
+    let parsed = parse(
+        "version: v2\njobs:\n  - name: build\n    script: |\n      make\n      make test\n  - name: lint\n    script: cargo clippy",
+    )
+    .unwrap();
+    let options = EmitOptions::new()
+        .with_style_at("version", ScalarStyle::Quoted)
+        .with_style_at("jobs.*.script", ScalarStyle::Literal)
+        .with_style_at("jobs.1.name", ScalarStyle::Quoted);
+    let output = emit_with(&parsed, &options);
+    assert_eq!(
+        output,
+        "version: \"v2\"\njobs:\n  - name: build\n    script: |\n      make\n      make test\n  - name: \"lint\"\n    script: |-\n      cargo clippy"
+    );
+    assert_eq!(parse(&output).unwrap().value, parsed.value);
+}
+
+#[test]
+fn test_style_override_fallbacks() {
+    let parsed = parse("a: \"1\"\nb: x\nc: x").unwrap();
+    let options = EmitOptions::new()
+        // Numbers may be written plain on request
+        .with_style_at("a", ScalarStyle::Plain)
+        // The last matching override wins
+        .with_style_at("*", ScalarStyle::Quoted)
+        .with_style_at("b", ScalarStyle::Folded)
+        .with_style_at("c", ScalarStyle::Plain);
+    assert_eq!(emit_with(&parsed, &options), "a: \"1\"\nb: >-\n  x\nc: x");
+
+    // Styles that can't hold a value fall back to the usual choice. A
+    // leading space only takes an indentation indicator, but a line of
+    // spaces would read back as an empty one
+    let mut node = parse("empty: x\nspaced: x\nblank_line: x").unwrap();
+    *node.get_mut("empty").unwrap() = YamlNode::from_value(YamlValue::String(String::new()));
+    *node.get_mut("spaced").unwrap() = YamlNode::from_value(YamlValue::String(" x\ny".into()));
+    *node.get_mut("blank_line").unwrap() =
+        YamlNode::from_value(YamlValue::String("x\n  \ny".into()));
+    let options = EmitOptions::new()
+        .with_style_at("empty", ScalarStyle::Plain)
+        .with_style_at("spaced", ScalarStyle::Literal)
+        .with_style_at("blank_line", ScalarStyle::Literal);
+    assert_eq!(
+        emit_with(&node, &options),
+        "empty: \"\"\nspaced: |2-\n   x\n  y\nblank_line: \"x\\n  \\ny\""
+    );
+}
+
+#[test]
+fn test_overrides_keep_collections_in_block_style() {
+    let parsed = parse("tags:\n  - v1\n  - v2\nos:\n  - linux").unwrap();
+    let options = EmitOptions::new()
+        .with_flow_style(FlowStyle::Short { max_width: 80 })
+        .with_style_at("tags.*", ScalarStyle::Quoted);
+    assert_eq!(
+        emit_with(&parsed, &options),
+        "tags:\n  - \"v1\"\n  - \"v2\"\nos: [linux]"
+    );
+}
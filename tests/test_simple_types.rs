@@ -26,17 +26,19 @@ fn test_basic_string_parsing() {
 
         let map = match &parsed.value {
             YamlValue::Object(m) => m,
-            YamlValue::String(_) | YamlValue::Array(_) => {
+            YamlValue::String(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
                 panic!("Expected YamlValue::Object, got {:?}", parsed.value)
             }
         };
         let value_node = map.get("value").expect("value key not found");
         let s = match &value_node.value {
             YamlValue::String(s) => s,
-            YamlValue::Object(_) | YamlValue::Array(_) => panic!(
-                "Expected YamlValue::String for input '{}', got {:?}",
-                input, value_node.value
-            ),
+            YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+                panic!(
+                    "Expected YamlValue::String for input '{}', got {:?}",
+                    input, value_node.value
+                )
+            }
         };
         assert_eq!(s.as_str(), expected, "Failed for input: {}", input);
     }
@@ -62,10 +64,12 @@ items:
     let items_node = map.get("items").expect("items key not found");
     let items = match &items_node.value {
         YamlValue::Array(arr) => arr,
-        YamlValue::String(_) | YamlValue::Object(_) => panic!(
-            "Expected YamlValue::Array for items, got {:?}",
-            items_node.value
-        ),
+        YamlValue::String(_) | YamlValue::Object(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+            panic!(
+                "Expected YamlValue::Array for items, got {:?}",
+                items_node.value
+            )
+        }
     };
     assert_eq!(items.len(), 5);
 
@@ -73,10 +77,12 @@ items:
     for (i, expected_val) in expected.iter().enumerate() {
         let s = match &items[i].value {
             YamlValue::String(s) => s,
-            YamlValue::Object(_) | YamlValue::Array(_) => panic!(
-                "Expected YamlValue::String at index {}, got {:?}",
-                i, items[i].value
-            ),
+            YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::Alias(_) | YamlValue::Blank => {
+                panic!(
+                    "Expected YamlValue::String at index {}, got {:?}",
+                    i, items[i].value
+                )
+            }
         };
         assert_eq!(s.as_str(), *expected_val);
     }
@@ -0,0 +1,84 @@
+#![deny(clippy::all)]
+
+use std::io;
+use yamp::{
+    emit, emit_to_fmt, emit_to_writer, emit_to_writer_with, parse, EmitOptions, LineEnding,
+};
+
+const CONFIG: &str = r#"# Inventory
+hosts:
+  - name: web-1 # Primary
+    roles:
+      - web
+      - cache
+  - name: db-1
+    roles:
+      - db
+notes: |
+  Rebuilt nightly.
+  Contact ops.
+# End of inventory
+"#;
+
+/// Fails after accepting `remaining` bytes
+struct FailingWriter {
+    remaining: usize,
+}
+
+impl io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"));
+        }
+        let written = buf.len().min(self.remaining);
+        self.remaining -= written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_writer_matches_emit() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    let mut bytes = Vec::new();
+    emit_to_writer(&parsed, &mut bytes).expect("Failed to write");
+    assert_eq!(String::from_utf8(bytes).unwrap(), emit(&parsed));
+
+    let mut text = String::new();
+    emit_to_fmt(&parsed, &mut text).expect("Failed to write");
+    assert_eq!(text, emit(&parsed));
+}
+
+#[test]
+fn test_writer_with_options() {
+    let parsed = parse("a: x\nb: y").expect("Failed to parse");
+    let options = EmitOptions::new().with_line_ending(LineEnding::CrLf);
+
+    let mut bytes = Vec::new();
+    emit_to_writer_with(&parsed, &mut bytes, &options).expect("Failed to write");
+    assert_eq!(bytes, b"a: x\r\nb: y");
+}
+
+#[test]
+fn test_writer_error_is_returned() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    let error = emit_to_writer(&parsed, FailingWriter { remaining: 10 }).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(error.to_string(), "disk full");
+}
+
+#[test]
+fn test_display() {
+    let parsed = parse(CONFIG).expect("Failed to parse");
+
+    assert_eq!(parsed.to_string(), emit(&parsed));
+    assert_eq!(
+        format!("{}", parsed.get("hosts").unwrap().as_array().unwrap()[1]),
+        "name: db-1\nroles:\n  - db"
+    );
+}
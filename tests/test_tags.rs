@@ -0,0 +1,117 @@
+#![deny(clippy::all)]
+
+use yamp::{emit, emit_checked, parse, YamlNode, YamlObject};
+
+fn tag<'n>(root: &'n YamlNode, path: &str) -> Option<&'n str> {
+    root.get_path(path).and_then(|node| node.tag.as_deref())
+}
+
+const TEMPLATE: &str = r#"Resources:
+  Bucket:
+    Type: "AWS::S3::Bucket"
+    Properties:
+      BucketName: !Sub "${AWS::StackName}-data"
+      Role: !GetAtt [Role, Arn] # Created below
+      Tags: !Split
+        - ","
+        - !Ref TagList
+      Policy: !Join ["", [!Ref Prefix, "-policy"]]"#;
+
+#[test]
+fn test_cloudformation_round_trip() {
+    let parsed = parse(TEMPLATE).unwrap();
+    let properties = parsed.get_path("Resources.Bucket.Properties").unwrap();
+
+    let name = properties.get("BucketName").unwrap();
+    assert_eq!(name.tag.as_deref(), Some("!Sub"));
+    assert_eq!(name.as_str(), Some("${AWS::StackName}-data"));
+    assert_eq!(tag(properties, "Role"), Some("!GetAtt"));
+    assert_eq!(
+        properties.get("Role").unwrap().as_array().map(Vec::len),
+        Some(2)
+    );
+    assert_eq!(tag(properties, "Tags"), Some("!Split"));
+    assert_eq!(tag(properties, "Tags.1"), Some("!Ref"));
+    assert_eq!(tag(properties, "Policy.1.0"), Some("!Ref"));
+
+    assert_eq!(emit_checked(&parsed).unwrap(), TEMPLATE);
+}
+
+#[test]
+fn test_tag_forms() {
+    let yaml = r#"password: !secret db_password
+port: !!str 8080
+name: !<tag:yaml.org,2002:str> api
+vault: !vault |
+  $ANSIBLE_VAULT;1.1;AES256
+  6134
+serve:
+  - !include routes.yaml
+  - !.git  # Hidden
+  - ! plain"#;
+    let parsed = parse(yaml).unwrap();
+    assert_eq!(tag(&parsed, "password"), Some("!secret"));
+    assert_eq!(tag(&parsed, "port"), Some("!!str"));
+    assert_eq!(tag(&parsed, "name"), Some("!<tag:yaml.org,2002:str>"));
+    assert_eq!(
+        parsed.get("vault").and_then(YamlNode::as_str),
+        Some("$ANSIBLE_VAULT;1.1;AES256\n6134\n")
+    );
+
+    // Tags aren't interpreted: values stay strings
+    assert_eq!(parsed.get("port").and_then(YamlNode::as_str), Some("8080"));
+
+    // A tag with nothing after it is on a blank node
+    let git = parsed.get_path("serve.1").unwrap();
+    assert_eq!(git.tag.as_deref(), Some("!.git"));
+    assert!(git.is_blank());
+    assert_eq!(git.inline_comment.as_deref(), Some("Hidden"));
+    assert_eq!(tag(&parsed, "serve.2"), Some("!"));
+
+    let output = emit(&parsed);
+    assert!(output.contains("  - !.git # Hidden\n"), "{}", output);
+    assert_eq!(parse(&output).unwrap(), parsed);
+}
+
+#[test]
+fn test_tags_on_collections() {
+    let parsed = parse("config: !!map\n  level: info\nitems:\n  - !point\n    x: \"1\"\n").unwrap();
+    assert_eq!(tag(&parsed, "config"), Some("!!map"));
+    assert_eq!(tag(&parsed, "items.0"), Some("!point"));
+    assert_eq!(tag(&parsed, "items.0.x"), None);
+    assert_eq!(
+        emit(&parsed),
+        "config: !!map\n  level: info\nitems:\n  - !point\n    x: \"1\""
+    );
+
+    // Built trees are written the same way
+    let node = YamlNode::from(
+        YamlObject::new()
+            .with("ref", YamlNode::from("Bucket").with_tag("!Ref"))
+            .with("blank", YamlNode::blank().with_tag("!!null"))
+            .with("empty", YamlNode::from("").with_tag("!!str")),
+    )
+    .with_tag("!!map");
+    assert_eq!(
+        emit_checked(&node).unwrap(),
+        "!!map\nref: !Ref Bucket\nblank: !!null\nempty: !!str \"\""
+    );
+}
+
+#[test]
+fn test_properties_on_keys_are_rejected() {
+    // On a key's line, a tag or anchor belongs to the key, which can't hold one
+    for yaml in ["!!str a: b", "items:\n  - !key name: x"] {
+        assert_eq!(
+            parse(yaml),
+            Err("Tags and anchors on mapping keys are not supported".to_string()),
+            "{:?}",
+            yaml
+        );
+    }
+
+    // On a line of their own they belong to the mapping
+    let parsed = parse("!!map\na: b").unwrap();
+    assert_eq!(parsed.tag.as_deref(), Some("!!map"));
+    assert_eq!(parsed.get("a").unwrap().tag, None);
+}